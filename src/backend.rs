@@ -0,0 +1,116 @@
+//! The abstraction of the window system a view is running on
+//!
+//! All the provided methods of
+//! [`PuglViewTrait`](../view/trait.PuglViewTrait.html) are implemented
+//! on top of the [`BackendImpl`](trait.BackendImpl.html) trait, so that the UI
+//! code behaves the same no matter whether it is driven by pugl or by
+//! the mock.
+
+use std::any::Any;
+
+use crate::types::*;
+use crate::pugl as p;
+use crate::view::PuglViewTrait;
+
+/// An event that a backend hands over to the view to be dispatched to the UI
+pub enum ViewEvent {
+    /// An input event, passed to `PuglViewTrait::event()`
+    Input(Event),
+    /// The view has received the focus
+    FocusIn,
+    /// The view has lost the focus
+    FocusOut,
+    /// The timer with the given id is due
+    Timer(usize),
+    /// The window system requests to close the view
+    Close,
+    /// A part of the view needs to be redrawn on the given context
+    Expose(ExposeArea, cairo::Context),
+    /// The view has been resized
+    Configure(Size),
+    /// The window manager may have changed the state of the window
+    WindowStateChanged,
+    /// All the events of an update have been dispatched, sent by the view
+    UpdateDone
+}
+
+/// A call of the backend that may dispatch events to the view
+///
+/// The events borrow the backend again, so the call is made after the
+/// borrow of the backend that returned it has ended. Backends that do
+/// not dispatch events by themselves just return the result.
+pub type DeferredCall = Box<dyn FnOnce() -> Status>;
+
+/// A backend a [`PuglView`](struct.PuglView.html) can run on
+///
+/// Implemented by [`PuglBackend`](struct.PuglBackend.html) and
+/// [`MockBackend`](struct.MockBackend.html). It cannot be implemented
+/// outside this crate.
+pub trait Backend: BackendImpl + 'static {}
+
+/// The functionality of a window system needed by a view
+///
+/// Methods returning a `Status` fail the same way for every backend,
+/// e.g. everything involving the pointer fails if the view is not
+/// realized.
+pub trait BackendImpl {
+    /// Creates the backend for a view of the UI type `T`
+    fn new<T: PuglViewTrait>(parent_window: *mut std::ffi::c_void) -> Self where Self: Sized;
+
+    /// Tells the backend the handle of the view it is serving
+    fn set_handle(&mut self, view: crate::view::PuglViewFFI);
+
+    fn world(&self) -> *mut p::PuglWorld;
+    fn native_window(&self) -> p::PuglNativeView;
+    fn is_realized(&self) -> bool;
+
+    fn post_redisplay(&mut self) -> Status;
+    fn post_redisplay_rect(&mut self, rect: Rect) -> Status;
+
+    fn frame(&self) -> Rect;
+    fn set_frame(&mut self, frame: Rect) -> Status;
+    fn monitors(&self) -> Vec<Monitor>;
+
+    fn set_default_size(&mut self, width: i32, height: i32) -> Status;
+    fn set_min_size(&mut self, width: i32, height: i32) -> Status;
+    fn set_max_size(&mut self, width: i32, height: i32) -> Status;
+    fn set_aspect_ratio(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Status;
+
+    fn view_hint(&self, hint: p::PuglViewHint) -> p::PuglViewHintValue;
+    fn set_view_hint(&mut self, hint: p::PuglViewHint, value: p::PuglViewHintValue) -> Status;
+
+    fn set_window_title(&mut self, title: &str) -> Status;
+    fn set_window_icon(&mut self, icons: &[WindowIcon]) -> Status;
+    fn set_window_state(&mut self, state: WindowState, enable: bool) -> Status;
+    /// Returns the state of the window, `None` if it is not known
+    /// because the view is not realized
+    fn window_state(&self) -> Option<WindowState>;
+
+    fn realize(&mut self) -> DeferredCall;
+    fn show_window(&mut self) -> DeferredCall;
+    fn hide_window(&mut self) -> Status;
+    fn is_visible(&self) -> bool;
+    fn request_attention(&mut self) -> Status;
+
+    fn grab_pointer(&mut self) -> Status;
+    fn ungrab_pointer(&mut self) -> Status;
+    fn set_pointer_position(&mut self, pos: Coord) -> Status;
+    fn set_cursor_visible(&mut self, visible: bool) -> Status;
+    fn set_cursor(&mut self, cursor: Cursor) -> Status;
+
+    /// Returns the time in seconds the timestamps of events refer to
+    fn time(&self) -> f64;
+
+    fn update(&mut self, timeout: f64) -> DeferredCall;
+    /// Returns the next event the backend has queued up during
+    /// [`update()`](#tymethod.update) to be dispatched by the view
+    ///
+    /// The view dispatches events until `None` is returned. Backends
+    /// that dispatch their events themselves return `None` for those.
+    fn next_event(&mut self) -> Option<ViewEvent>;
+
+    fn start_timer(&mut self, id: usize, timeout: f64) -> Status;
+    fn stop_timer(&mut self, id: usize) -> Status;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
@@ -0,0 +1,249 @@
+//! Tracking of mouse drag gestures
+
+use crate::types::*;
+
+/// A step of a drag gesture reported by [`DragTracker`](struct.DragTracker.html)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DragEvent {
+    /// The drag button has been pressed at the given position
+    Started(Coord),
+    /// The pointer has moved while the drag button is pressed
+    ///
+    /// `delta` is the distance from the position where the drag started.
+    Moved {
+        /// The position where the drag started
+        origin: Coord,
+        /// The current pointer position
+        pos: Coord,
+        /// The distance from `origin` to `pos`
+        delta: Coord,
+        /// The keyboard modifiers during the motion
+        modifiers: Modifiers
+    },
+    /// The drag has ended
+    ///
+    /// `delta` is the distance from the position where the drag started
+    /// to the last known pointer position.
+    Ended {
+        /// The position where the drag started
+        origin: Coord,
+        /// The distance from `origin` to the last pointer position
+        delta: Coord
+    }
+}
+
+/// Keeps track of a drag gesture of a mouse button
+///
+/// A drag starts with a press of the button, reports the motion
+/// relative to the position of the press, and ends when the button is
+/// released or the view loses the focus. Widgets like knobs and
+/// sliders pass all the events they get to
+/// [`event()`](#method.event) and to [`focus_out()`](#method.focus_out).
+///
+/// In relative pointer mode the pointer stays in place and the
+/// distances of `RelativeMove` events are added up to a virtual
+/// pointer position instead, so a drag can go on beyond the borders
+/// of the view and the screen.
+///
+/// ```
+/// use pugl_sys::*;
+///
+/// let mut drag = DragTracker::new(Button::Left);
+/// let press = Event {
+///     data: EventType::MouseButtonPress(MouseButton {
+///         button: Button::Left,
+///         modifiers: Modifiers::NONE,
+///         click_count: 1
+///     }),
+///     context: EventContext { pos: Coord { x: 10., y: 20. }, ..Default::default() }
+/// };
+/// let motion = Event {
+///     data: EventType::MouseMove(MotionContext::default()),
+///     context: EventContext { pos: Coord { x: 15., y: 10. }, ..Default::default() }
+/// };
+///
+/// assert_eq!(drag.event(&press), Some(DragEvent::Started(Coord { x: 10., y: 20. })));
+/// match drag.event(&motion) {
+///     Some(DragEvent::Moved { delta, .. }) => assert_eq!(delta, Coord { x: 5., y: -10. }),
+///     _ => panic!("expected motion")
+/// }
+/// assert_eq!(drag.focus_out(), Some(DragEvent::Ended {
+///     origin: Coord { x: 10., y: 20. },
+///     delta: Coord { x: 5., y: -10. }
+/// }));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DragTracker {
+    button: Button,
+    origin: Option<Coord>,
+    last_pos: Coord,
+    relative: bool
+}
+
+impl DragTracker {
+    /// Creates a new `DragTracker` for drags with `button`
+    pub fn new(button: Button) -> DragTracker {
+        DragTracker {
+            button,
+            origin: None,
+            last_pos: Coord::default(),
+            relative: false
+        }
+    }
+
+    /// Returns the position where the current drag started,
+    /// `None` if there is no drag going on
+    pub fn origin(&self) -> Option<Coord> {
+        self.origin
+    }
+
+    /// Returns `true` if a drag is going on
+    pub fn is_dragging(&self) -> bool {
+        self.origin.is_some()
+    }
+
+    /// Processes `ev` and returns the resulting step of the drag, if any
+    pub fn event(&mut self, ev: &Event) -> Option<DragEvent> {
+        match ev.data {
+            EventType::MouseButtonPress(mb) if mb.button == self.button && self.origin.is_none() => {
+                self.origin = Some(ev.context.pos);
+                self.last_pos = ev.context.pos;
+                self.relative = false;
+                Some(DragEvent::Started(ev.context.pos))
+            }
+            EventType::MouseMove(motion) => self.moved(ev.context.pos, motion.modifiers),
+            EventType::RelativeMove(motion) if self.origin.is_some() => {
+                self.relative = true;
+                let pos = self.last_pos + motion.delta;
+                self.moved(pos, motion.modifiers)
+            }
+            EventType::MouseButtonRelease(mb) if mb.button == self.button => {
+                // in relative pointer mode the release is reported at the anchor
+                if !self.relative {
+                    self.last_pos = ev.context.pos;
+                }
+                self.end()
+            }
+            _ => None
+        }
+    }
+
+    /// Ends a drag going on because the view has lost the focus
+    pub fn focus_out(&mut self) -> Option<DragEvent> {
+        self.end()
+    }
+
+    fn moved(&mut self, pos: Coord, modifiers: Modifiers) -> Option<DragEvent> {
+        let origin = self.origin?;
+        self.last_pos = pos;
+        Some(DragEvent::Moved { origin, pos, delta: pos - origin, modifiers })
+    }
+
+    fn end(&mut self) -> Option<DragEvent> {
+        let origin = self.origin.take()?;
+        Some(DragEvent::Ended { origin, delta: self.last_pos - origin })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn button_event(press: bool, button: Button, x: f64, y: f64) -> Event {
+        let mb = MouseButton { button, modifiers: Modifiers::NONE, click_count: 1 };
+        Event {
+            data: if press { EventType::MouseButtonPress(mb) } else { EventType::MouseButtonRelease(mb) },
+            context: EventContext { pos: Coord { x, y }, ..Default::default() }
+        }
+    }
+
+    fn motion_event(x: f64, y: f64) -> Event {
+        Event {
+            data: EventType::MouseMove(MotionContext { modifiers: Modifiers::SHIFT, ..Default::default() }),
+            context: EventContext { pos: Coord { x, y }, ..Default::default() }
+        }
+    }
+
+    #[test]
+    fn drag_press_move_release() {
+        let mut drag = DragTracker::new(Button::Left);
+        assert_eq!(drag.event(&button_event(true, Button::Left, 10., 10.)), Some(DragEvent::Started(Coord { x: 10., y: 10. })));
+        assert!(drag.is_dragging());
+        assert_eq!(drag.event(&motion_event(20., 5.)), Some(DragEvent::Moved {
+            origin: Coord { x: 10., y: 10. },
+            pos: Coord { x: 20., y: 5. },
+            delta: Coord { x: 10., y: -5. },
+            modifiers: Modifiers::SHIFT
+        }));
+        assert_eq!(drag.event(&button_event(false, Button::Left, 30., 0.)), Some(DragEvent::Ended {
+            origin: Coord { x: 10., y: 10. },
+            delta: Coord { x: 20., y: -10. }
+        }));
+        assert!(!drag.is_dragging());
+        assert_eq!(drag.event(&motion_event(20., 5.)), None);
+    }
+
+    fn relative_motion_event(dx: f64, dy: f64) -> Event {
+        Event {
+            data: EventType::RelativeMove(RelativeMotion {
+                delta: Coord { x: dx, y: dy },
+                modifiers: Modifiers::CTRL,
+                buttons: MouseButtons::LEFT
+            }),
+            // the pointer is kept at the anchor
+            context: EventContext { pos: Coord { x: 10., y: 10. }, ..Default::default() }
+        }
+    }
+
+    #[test]
+    fn drag_relative_motion() {
+        let mut drag = DragTracker::new(Button::Left);
+        assert_eq!(drag.event(&relative_motion_event(5., 5.)), None);
+        drag.event(&button_event(true, Button::Left, 10., 10.));
+        assert_eq!(drag.event(&relative_motion_event(3., -4.)), Some(DragEvent::Moved {
+            origin: Coord { x: 10., y: 10. },
+            pos: Coord { x: 13., y: 6. },
+            delta: Coord { x: 3., y: -4. },
+            modifiers: Modifiers::CTRL
+        }));
+        assert_eq!(drag.event(&relative_motion_event(3., -4.)), Some(DragEvent::Moved {
+            origin: Coord { x: 10., y: 10. },
+            pos: Coord { x: 16., y: 2. },
+            delta: Coord { x: 6., y: -8. },
+            modifiers: Modifiers::CTRL
+        }));
+        assert_eq!(drag.event(&button_event(false, Button::Left, 10., 10.)), Some(DragEvent::Ended {
+            origin: Coord { x: 10., y: 10. },
+            delta: Coord { x: 6., y: -8. }
+        }));
+
+        drag.event(&button_event(true, Button::Left, 10., 10.));
+        assert_eq!(drag.event(&button_event(false, Button::Left, 12., 10.)), Some(DragEvent::Ended {
+            origin: Coord { x: 10., y: 10. },
+            delta: Coord { x: 2., y: 0. }
+        }));
+    }
+
+    #[test]
+    fn drag_other_button_ignored() {
+        let mut drag = DragTracker::new(Button::Left);
+        assert_eq!(drag.event(&button_event(true, Button::Right, 10., 10.)), None);
+        assert_eq!(drag.event(&motion_event(20., 5.)), None);
+        drag.event(&button_event(true, Button::Left, 10., 10.));
+        assert_eq!(drag.event(&button_event(false, Button::Right, 10., 10.)), None);
+        assert!(drag.is_dragging());
+    }
+
+    #[test]
+    fn drag_ends_on_focus_out() {
+        let mut drag = DragTracker::new(Button::Left);
+        assert_eq!(drag.focus_out(), None);
+        drag.event(&button_event(true, Button::Left, 10., 10.));
+        drag.event(&motion_event(12., 10.));
+        assert_eq!(drag.focus_out(), Some(DragEvent::Ended {
+            origin: Coord { x: 10., y: 10. },
+            delta: Coord { x: 2., y: 0. }
+        }));
+        assert_eq!(drag.origin(), None);
+    }
+}
@@ -0,0 +1,576 @@
+//! Processing of input events that pugl does not do itself
+//!
+//! Every view keeps an `InputState` and passes all the input events
+//! through it before they are handed to the UI.
+
+use std::collections::HashMap;
+
+use crate::types::*;
+
+/// Default maximum time between two clicks of a multi click in seconds
+pub(crate) const DOUBLE_CLICK_TIME: f64 = 0.4;
+
+/// Default maximum distance in both directions between two clicks of a multi click
+pub(crate) const DOUBLE_CLICK_DISTANCE: f64 = 5.0;
+
+/// Maximum time between two smooth scroll events of the same gesture in seconds
+pub(crate) const SCROLL_GESTURE_GAP: f64 = 0.15;
+
+/// Counts the clicks of the same mouse button at the same position
+pub(crate) struct ClickCounter {
+    /// Maximum time between two clicks in seconds
+    pub(crate) time: f64,
+    /// Maximum distance between two clicks
+    pub(crate) distance: f64,
+    last_press: Option<(Button, Coord, f64)>,
+    count: u32
+}
+
+impl Default for ClickCounter {
+    fn default() -> Self {
+        ClickCounter {
+            time: DOUBLE_CLICK_TIME,
+            distance: DOUBLE_CLICK_DISTANCE,
+            last_press: None,
+            count: 0
+        }
+    }
+}
+
+impl ClickCounter {
+    /// Registers a press of `button` and returns the click count
+    fn press(&mut self, button: Button, context: &EventContext) -> u32 {
+        let continued = match self.last_press {
+            Some((last_button, pos, time)) => {
+                last_button == button
+                    && context.time - time <= self.time
+                    && (context.pos.x - pos.x).abs() <= self.distance
+                    && (context.pos.y - pos.y).abs() <= self.distance
+            }
+            None => false
+        };
+        self.count = if continued { self.count + 1 } else { 1 };
+        self.last_press = Some((button, context.pos, context.time));
+        self.count
+    }
+
+    /// Returns the click count for a release of `button`
+    fn release(&self, button: Button) -> u32 {
+        match self.last_press {
+            Some((last_button, _, _)) if last_button == button => self.count,
+            _ => 1
+        }
+    }
+}
+
+/// The input state of a view
+#[derive(Default)]
+pub(crate) struct InputState {
+    pub(crate) clicks: ClickCounter,
+    buttons: MouseButtons,
+    modifiers: Modifiers,
+    /// The pressed keys with the value they were pressed with
+    pressed_keys: HashMap<PressedKey, KeyVal>,
+    /// The last smooth scroll of the running scroll gesture
+    scroll_gesture: Option<Event>,
+    pointer_pos: Coord,
+    relative_anchor: Option<Coord>
+}
+
+/// Identifies a pressed key
+///
+/// Keys are identified by their key code, as a release can carry a
+/// different value than the press, e.g. when a modifier is pressed in
+/// between. The value is used only if the key code is not known.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum PressedKey {
+    Code(u32),
+    Value(KeyVal)
+}
+
+impl PressedKey {
+    fn of(key: &Key) -> PressedKey {
+        match key.code {
+            0 => PressedKey::Value(key.key),
+            code => PressedKey::Code(code)
+        }
+    }
+}
+
+/// Returns the modifier that is held by `key`, if any
+fn modifier_of_key(key: KeyVal) -> Modifiers {
+    match key {
+        KeyVal::Special(SpecialKey::ShiftL) | KeyVal::Special(SpecialKey::ShiftR) => Modifiers::SHIFT,
+        KeyVal::Special(SpecialKey::CtrlL) | KeyVal::Special(SpecialKey::CtrlR) => Modifiers::CTRL,
+        KeyVal::Special(SpecialKey::AltL) | KeyVal::Special(SpecialKey::AltR) => Modifiers::ALT,
+        KeyVal::Special(SpecialKey::SuperL) | KeyVal::Special(SpecialKey::SuperR) => Modifiers::SUPER,
+        _ => Modifiers::NONE
+    }
+}
+
+impl InputState {
+    /// Returns the modifiers currently held
+    pub(crate) fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Returns the mouse buttons currently pressed
+    pub(crate) fn buttons(&self) -> MouseButtons {
+        self.buttons
+    }
+
+    /// Returns `true` if a key is currently pressed that was pressed as `key`
+    pub(crate) fn is_key_pressed(&self, key: KeyVal) -> bool {
+        self.pressed_keys.values().any(|&k| k == key)
+    }
+
+    /// Returns `true` if the physical key `key` is currently pressed
+    pub(crate) fn is_physical_key_pressed(&self, key: PhysicalKey) -> bool {
+        self.pressed_keys.contains_key(&PressedKey::Code(key.keycode()))
+    }
+
+    /// Forgets about all pressed keys and buttons
+    ///
+    /// To be called when the view loses the focus, as the release
+    /// events will not be reported to the view.
+    pub(crate) fn reset(&mut self) {
+        self.buttons = MouseButtons::NONE;
+        self.modifiers = Modifiers::NONE;
+        self.pressed_keys.clear();
+        self.scroll_gesture = None;
+    }
+
+    /// Returns the position the pointer is warped back to in relative pointer mode,
+    /// `None` if the relative pointer mode is off
+    pub(crate) fn relative_anchor(&self) -> Option<Coord> {
+        self.relative_anchor
+    }
+
+    /// Switches the relative pointer mode on or off
+    ///
+    /// When switched on, the current pointer position becomes the anchor.
+    pub(crate) fn set_relative_mode(&mut self, enable: bool) {
+        self.relative_anchor = match enable {
+            true => Some(self.relative_anchor.unwrap_or(self.pointer_pos)),
+            false => None
+        };
+    }
+
+    /// Updates the state by `ev` and returns `ev` completed by the state
+    ///
+    /// Returns `None` if the event is not to be passed to the UI, like
+    /// the motion caused by warping the pointer back to the anchor in
+    /// relative pointer mode.
+    pub(crate) fn process(&mut self, mut ev: Event) -> Option<Event> {
+        self.update_modifiers(&ev);
+        if let (Some(anchor), EventType::MouseMove(motion)) = (self.relative_anchor, ev.data) {
+            let delta = ev.context.pos - anchor;
+            if delta == Coord::default() {
+                return None;
+            }
+            ev.data = EventType::RelativeMove(RelativeMotion {
+                delta,
+                modifiers: motion.modifiers,
+                buttons: self.buttons
+            });
+            return Some(ev);
+        }
+        self.pointer_pos = ev.context.pos;
+        match ev.data {
+            EventType::MouseButtonPress(ref mut button) => {
+                button.click_count = self.clicks.press(button.button, &ev.context);
+                self.buttons.insert(button.button.into());
+            }
+            EventType::MouseButtonRelease(ref mut button) => {
+                button.click_count = self.clicks.release(button.button);
+                self.buttons.remove(button.button.into());
+            }
+            EventType::MouseMove(ref mut motion) => {
+                motion.buttons = self.buttons;
+            }
+            EventType::KeyPress(ref mut key) => {
+                key.is_repeat = self.pressed_keys.insert(PressedKey::of(key), key.key).is_some();
+            }
+            EventType::KeyRelease(key) => {
+                self.pressed_keys.remove(&PressedKey::of(&key));
+            }
+            EventType::Scroll(ref mut scroll) => {
+                scroll.phase = self.scroll_phase(scroll, ev.context.time);
+            }
+            _ => {}
+        }
+        if let EventType::Scroll(scroll) = ev.data {
+            self.scroll_gesture = if scroll.is_smooth() { Some(ev) } else { None };
+        }
+        Some(ev)
+    }
+
+    fn scroll_phase(&self, scroll: &Scroll, time: f64) -> ScrollPhase {
+        if !scroll.is_smooth() {
+            return ScrollPhase::None;
+        }
+        match self.scroll_gesture {
+            Some(last) if time - last.context.time <= SCROLL_GESTURE_GAP => ScrollPhase::Update,
+            _ => ScrollPhase::Begin
+        }
+    }
+
+    /// Returns `true` if a scroll gesture is running that is over
+    ///
+    /// A gesture is over when `next` is not a smooth scroll, or if no
+    /// smooth scroll has followed within the gesture gap until the time
+    /// of `next` or, if there is no next event, until `time`.
+    pub(crate) fn is_scroll_gesture_over(&self, next: Option<&Event>, time: f64) -> bool {
+        let last = match self.scroll_gesture {
+            Some(last) => last,
+            None => return false
+        };
+        match next {
+            Some(ev) => match ev.data {
+                EventType::Scroll(scroll) if scroll.is_smooth() => ev.context.time - last.context.time > SCROLL_GESTURE_GAP,
+                _ => true
+            },
+            None => time - last.context.time > SCROLL_GESTURE_GAP
+        }
+    }
+
+    /// Ends the running scroll gesture and returns the event telling so at `time`
+    pub(crate) fn end_scroll_gesture(&mut self, time: f64) -> Option<Event> {
+        let mut end = self.scroll_gesture.take()?;
+        if let EventType::Scroll(ref mut scroll) = end.data {
+            scroll.dx = 0.;
+            scroll.dy = 0.;
+            scroll.phase = ScrollPhase::End;
+        }
+        end.context.time = time;
+        Some(end)
+    }
+
+    fn update_modifiers(&mut self, ev: &Event) {
+        // The modifiers of an event are the ones held before the
+        // event, so the modifier keys themselves need to be applied.
+        self.modifiers = match ev.data {
+            EventType::KeyPress(key) => key.modifiers | modifier_of_key(key.key),
+            EventType::KeyRelease(key) => key.modifiers - modifier_of_key(key.key),
+            EventType::MouseButtonPress(button) | EventType::MouseButtonRelease(button) => button.modifiers,
+            EventType::MouseMove(motion) => motion.modifiers,
+            EventType::Scroll(scroll) => scroll.modifiers,
+            _ => self.modifiers
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn button_event(press: bool, num: u32, x: f64, time: f64) -> Event {
+        let button = MouseButton { button: Button::from(num), modifiers: Modifiers::NONE, click_count: 1 };
+        Event {
+            data: if press {
+                EventType::MouseButtonPress(button)
+            } else {
+                EventType::MouseButtonRelease(button)
+            },
+            context: EventContext {
+                pos: Coord { x, y: 10. },
+                pos_root: Coord { x, y: 10. },
+                time,
+                flags: EventFlags::NONE
+            }
+        }
+    }
+
+    fn click_count(ev: Option<Event>) -> u32 {
+        match ev.expect("event swallowed").data {
+            EventType::MouseButtonPress(b) | EventType::MouseButtonRelease(b) => b.click_count,
+            _ => panic!("not a button event")
+        }
+    }
+
+    #[test]
+    fn single_click() {
+        let mut input = InputState::default();
+        assert_eq!(click_count(input.process(button_event(true, 1, 10., 1.0))), 1);
+        assert_eq!(click_count(input.process(button_event(false, 1, 10., 1.1))), 1);
+    }
+
+    #[test]
+    fn triple_click() {
+        let mut input = InputState::default();
+        assert_eq!(click_count(input.process(button_event(true, 1, 10., 1.0))), 1);
+        assert_eq!(click_count(input.process(button_event(false, 1, 10., 1.1))), 1);
+        assert_eq!(click_count(input.process(button_event(true, 1, 12., 1.2))), 2);
+        assert_eq!(click_count(input.process(button_event(false, 1, 12., 1.3))), 2);
+        assert_eq!(click_count(input.process(button_event(true, 1, 11., 1.4))), 3);
+        assert_eq!(click_count(input.process(button_event(false, 1, 11., 1.5))), 3);
+    }
+
+    #[test]
+    fn clicks_too_slow() {
+        let mut input = InputState::default();
+        input.process(button_event(true, 1, 10., 1.0));
+        input.process(button_event(false, 1, 10., 1.1));
+        assert_eq!(click_count(input.process(button_event(true, 1, 10., 1.5))), 1);
+    }
+
+    #[test]
+    fn clicks_too_far_apart() {
+        let mut input = InputState::default();
+        input.process(button_event(true, 1, 10., 1.0));
+        input.process(button_event(false, 1, 10., 1.1));
+        assert_eq!(click_count(input.process(button_event(true, 1, 20., 1.2))), 1);
+    }
+
+    #[test]
+    fn clicks_different_buttons() {
+        let mut input = InputState::default();
+        input.process(button_event(true, 1, 10., 1.0));
+        input.process(button_event(false, 1, 10., 1.1));
+        assert_eq!(click_count(input.process(button_event(true, 3, 10., 1.2))), 1);
+        assert_eq!(click_count(input.process(button_event(false, 1, 10., 1.3))), 1);
+    }
+
+    fn motion_event() -> Event {
+        Event {
+            data: EventType::MouseMove(MotionContext::default()),
+            context: EventContext::default()
+        }
+    }
+
+    fn motion_event_at(x: f64, y: f64) -> Event {
+        Event {
+            data: EventType::MouseMove(MotionContext::default()),
+            context: EventContext { pos: Coord { x, y }, ..Default::default() }
+        }
+    }
+
+    fn motion_buttons(ev: Option<Event>) -> MouseButtons {
+        match ev.expect("event swallowed").data {
+            EventType::MouseMove(motion) => motion.buttons,
+            _ => panic!("not a motion event")
+        }
+    }
+
+    #[test]
+    fn buttons_during_motion() {
+        let mut input = InputState::default();
+        assert_eq!(motion_buttons(input.process(motion_event())), MouseButtons::NONE);
+        input.process(button_event(true, 1, 10., 1.0));
+        input.process(button_event(true, 3, 10., 1.1));
+        assert_eq!(motion_buttons(input.process(motion_event())), MouseButtons::LEFT | MouseButtons::RIGHT);
+        input.process(button_event(false, 1, 10., 1.2));
+        assert_eq!(motion_buttons(input.process(motion_event())), MouseButtons::RIGHT);
+    }
+
+    #[test]
+    fn relative_motion() {
+        let mut input = InputState::default();
+        input.process(motion_event_at(10., 20.));
+        input.set_relative_mode(true);
+        assert_eq!(input.relative_anchor(), Some(Coord { x: 10., y: 20. }));
+        input.process(button_event(true, 1, 10., 1.0));
+
+        let ev = input.process(motion_event_at(13., 18.)).unwrap();
+        assert_eq!(ev.data, EventType::RelativeMove(RelativeMotion {
+            delta: Coord { x: 3., y: -2. },
+            modifiers: Modifiers::NONE,
+            buttons: MouseButtons::LEFT
+        }));
+        // motion by warping back to the anchor
+        assert_eq!(input.process(motion_event_at(10., 20.)), None);
+
+        input.set_relative_mode(false);
+        assert_eq!(input.relative_anchor(), None);
+        assert_eq!(motion_buttons(input.process(motion_event_at(13., 18.))), MouseButtons::LEFT);
+        input.set_relative_mode(true);
+        assert_eq!(input.relative_anchor(), Some(Coord { x: 13., y: 18. }));
+    }
+
+    fn key_event(press: bool, key: KeyVal, modifiers: Modifiers) -> Event {
+        let key = Key { key, modifiers, code: 0, is_repeat: false };
+        Event {
+            data: if press { EventType::KeyPress(key) } else { EventType::KeyRelease(key) },
+            context: EventContext::default()
+        }
+    }
+
+    #[test]
+    fn key_state() {
+        let mut input = InputState::default();
+        let shift = KeyVal::Special(SpecialKey::ShiftL);
+        input.process(key_event(true, shift, Modifiers::NONE));
+        assert_eq!(input.modifiers(), Modifiers::SHIFT);
+        input.process(key_event(true, KeyVal::Character('a'), Modifiers::SHIFT));
+        assert!(input.is_key_pressed(shift));
+        assert!(input.is_key_pressed(KeyVal::Character('a')));
+
+        input.process(key_event(false, shift, Modifiers::SHIFT));
+        assert_eq!(input.modifiers(), Modifiers::NONE);
+        assert!(!input.is_key_pressed(shift));
+        assert!(input.is_key_pressed(KeyVal::Character('a')));
+
+        input.process(key_event(false, KeyVal::Character('a'), Modifiers::NONE));
+        assert!(!input.is_key_pressed(KeyVal::Character('a')));
+    }
+
+    fn is_repeat(ev: Option<Event>) -> bool {
+        match ev.expect("event swallowed").data {
+            EventType::KeyPress(key) => key.is_repeat,
+            _ => panic!("not a key press")
+        }
+    }
+
+    #[test]
+    fn key_repeat() {
+        let mut input = InputState::default();
+        let a = KeyVal::Character('a');
+        assert!(!is_repeat(input.process(key_event(true, a, Modifiers::NONE))));
+        assert!(is_repeat(input.process(key_event(true, a, Modifiers::NONE))));
+        assert!(is_repeat(input.process(key_event(true, a, Modifiers::NONE))));
+        input.process(key_event(false, a, Modifiers::NONE));
+        assert!(!is_repeat(input.process(key_event(true, a, Modifiers::NONE))));
+        input.reset();
+        assert!(!is_repeat(input.process(key_event(true, a, Modifiers::NONE))));
+    }
+
+    fn coded_key_event(press: bool, key: KeyVal, code: u32) -> Event {
+        let key = Key { key, modifiers: Modifiers::NONE, code, is_repeat: false };
+        Event {
+            data: if press { EventType::KeyPress(key) } else { EventType::KeyRelease(key) },
+            context: EventContext::default()
+        }
+    }
+
+    #[test]
+    fn key_repeat_by_key_code() {
+        let mut input = InputState::default();
+        // released as 'A' after Shift was pressed in between
+        assert!(!is_repeat(input.process(coded_key_event(true, KeyVal::Character('a'), 38))));
+        input.process(coded_key_event(false, KeyVal::Character('A'), 38));
+        assert!(!input.is_key_pressed(KeyVal::Character('a')));
+        assert!(!is_repeat(input.process(coded_key_event(true, KeyVal::Character('a'), 38))));
+        assert!(is_repeat(input.process(coded_key_event(true, KeyVal::Character('A'), 38))));
+        assert!(input.is_key_pressed(KeyVal::Character('A')));
+
+        // same value, different keys
+        assert!(!is_repeat(input.process(coded_key_event(true, KeyVal::Character('1'), 10))));
+        assert!(!is_repeat(input.process(coded_key_event(true, KeyVal::Character('1'), 87))));
+        input.process(coded_key_event(false, KeyVal::Character('1'), 10));
+        assert!(input.is_key_pressed(KeyVal::Character('1')));
+    }
+
+    #[test]
+    fn physical_key_state() {
+        let mut input = InputState::default();
+        let z = PhysicalKey::KeyZ;
+        input.process(coded_key_event(true, KeyVal::Character('y'), z.keycode()));
+        assert!(input.is_physical_key_pressed(z));
+        assert!(!input.is_physical_key_pressed(PhysicalKey::KeyY));
+        input.process(coded_key_event(false, KeyVal::Character('Y'), z.keycode()));
+        assert!(!input.is_physical_key_pressed(z));
+        assert!(!input.is_key_pressed(KeyVal::Character('y')));
+
+        input.process(coded_key_event(true, KeyVal::Character('y'), z.keycode()));
+        input.reset();
+        assert!(!input.is_physical_key_pressed(z));
+
+        // keys without key code are not known physically
+        input.process(key_event(true, KeyVal::Character('z'), Modifiers::NONE));
+        assert!(!input.is_physical_key_pressed(z));
+        assert!(!input.is_physical_key_pressed(PhysicalKey::Unknown(0)));
+    }
+
+    fn scroll_event(direction: ScrollDirection, time: f64) -> Event {
+        Event {
+            data: EventType::Scroll(Scroll {
+                dx: 0.,
+                dy: 0.5,
+                modifiers: Modifiers::NONE,
+                direction,
+                phase: ScrollPhase::None
+            }),
+            context: EventContext { time, ..Default::default() }
+        }
+    }
+
+    fn scroll_phase(ev: Option<Event>) -> ScrollPhase {
+        match ev.expect("event swallowed").data {
+            EventType::Scroll(scroll) => scroll.phase,
+            _ => panic!("not a scroll event")
+        }
+    }
+
+    #[test]
+    fn smooth_scroll_phases() {
+        let mut input = InputState::default();
+        assert_eq!(scroll_phase(input.process(scroll_event(ScrollDirection::Smooth, 1.0))), ScrollPhase::Begin);
+        assert_eq!(scroll_phase(input.process(scroll_event(ScrollDirection::Smooth, 1.1))), ScrollPhase::Update);
+        assert_eq!(scroll_phase(input.process(scroll_event(ScrollDirection::Smooth, 1.2))), ScrollPhase::Update);
+        assert_eq!(scroll_phase(input.process(scroll_event(ScrollDirection::Smooth, 2.0))), ScrollPhase::Begin);
+        assert_eq!(scroll_phase(input.process(scroll_event(ScrollDirection::Up, 2.1))), ScrollPhase::None);
+        assert_eq!(scroll_phase(input.process(scroll_event(ScrollDirection::Smooth, 2.15))), ScrollPhase::Begin);
+    }
+
+    #[test]
+    fn scroll_gesture_end() {
+        let mut input = InputState::default();
+        assert!(!input.is_scroll_gesture_over(Some(&motion_event()), 0.));
+        assert_eq!(input.end_scroll_gesture(0.), None);
+
+        input.process(scroll_event(ScrollDirection::Smooth, 1.0));
+        assert!(!input.is_scroll_gesture_over(Some(&scroll_event(ScrollDirection::Smooth, 1.1)), 1.1));
+        assert!(!input.is_scroll_gesture_over(None, 1.1));
+        assert!(input.is_scroll_gesture_over(None, 1.2));
+        assert!(input.is_scroll_gesture_over(Some(&scroll_event(ScrollDirection::Smooth, 1.2)), 1.2));
+        assert!(input.is_scroll_gesture_over(Some(&scroll_event(ScrollDirection::Up, 1.1)), 1.1));
+        assert!(input.is_scroll_gesture_over(Some(&motion_event()), 1.1));
+
+        let end = input.end_scroll_gesture(1.1).unwrap();
+        assert_eq!(end.context.time, 1.1);
+        match end.data {
+            EventType::Scroll(scroll) => {
+                assert_eq!(scroll.phase, ScrollPhase::End);
+                assert_eq!((scroll.dx, scroll.dy), (0., 0.));
+                assert!(scroll.is_smooth());
+            }
+            _ => panic!("not a scroll event")
+        }
+        assert_eq!(input.end_scroll_gesture(1.2), None);
+        assert!(!input.is_scroll_gesture_over(None, 2.));
+    }
+
+    #[test]
+    fn modifiers_from_pointer_events() {
+        let mut input = InputState::default();
+        input.process(Event {
+            data: EventType::MouseMove(MotionContext { modifiers: Modifiers::CTRL, ..Default::default() }),
+            context: EventContext::default()
+        });
+        assert_eq!(input.modifiers(), Modifiers::CTRL);
+    }
+
+    #[test]
+    fn reset_state() {
+        let mut input = InputState::default();
+        input.process(key_event(true, KeyVal::Special(SpecialKey::CtrlR), Modifiers::NONE));
+        input.process(key_event(true, KeyVal::Character('x'), Modifiers::CTRL));
+        input.process(button_event(true, 1, 10., 1.0));
+        assert_eq!(input.buttons(), MouseButtons::LEFT);
+
+        input.reset();
+        assert_eq!(input.modifiers(), Modifiers::NONE);
+        assert_eq!(input.buttons(), MouseButtons::NONE);
+        assert!(!input.is_key_pressed(KeyVal::Character('x')));
+        assert_eq!(motion_buttons(input.process(motion_event())), MouseButtons::NONE);
+    }
+
+    #[test]
+    fn custom_double_click_time() {
+        let mut input = InputState::default();
+        input.clicks.time = 1.0;
+        input.process(button_event(true, 1, 10., 1.0));
+        input.process(button_event(false, 1, 10., 1.1));
+        assert_eq!(click_count(input.process(button_event(true, 1, 10., 1.8))), 2);
+    }
+}
@@ -129,10 +129,14 @@
 
 extern crate cairo;
 extern crate cairo_sys;
+extern crate x11;
 
 #[macro_use]
 extern crate bitflags;
 
+#[cfg(feature = "serde")]
+extern crate serde;
+
 #[cfg(test)]
 #[macro_use]
 extern crate serial_test;
@@ -145,6 +149,10 @@ extern crate mockall_double;
 #[doc(hidden)]
 pub(crate) mod pugl;
 
+pub(crate) mod native;
+
+pub(crate) mod input;
+
 #[doc(hidden)]
 #[macro_use]
 pub mod types;
@@ -153,18 +161,39 @@ pub mod types;
 pub use types::*;
 
 #[doc(hidden)]
-#[cfg(not(feature="testing"))]
+pub mod drag;
+
+#[doc(inline)]
+pub use drag::*;
+
+#[doc(hidden)]
+pub mod shortcut;
+
+#[doc(inline)]
+pub use shortcut::*;
+
+pub(crate) mod backend;
+
+pub(crate) mod snapshot;
+
+#[cfg(feature = "serde")]
+mod serde_support;
+
+#[doc(hidden)]
+pub mod record;
+
+#[doc(inline)]
+pub use record::*;
+
+#[doc(hidden)]
 pub mod view;
 
 #[doc(inline)]
-#[cfg(not(feature="testing"))]
 pub use view::*;
 
 
 #[doc(hidden)]
-#[cfg(feature="testing")]
 pub mod view_test;
 
 #[doc(inline)]
-#[cfg(feature="testing")]
 pub use view_test::*;
@@ -0,0 +1,487 @@
+//! Window system functionality that pugl does not provide
+//!
+//! So far only X11 is supported. All the functions take the native
+//! world (the X11 `Display`) and the native window of a realized view.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_int, c_long, c_ulong, c_void};
+
+use x11::xfixes;
+use x11::xlib;
+use x11::xrandr;
+
+use crate::types::*;
+use crate::pugl as p;
+
+unsafe fn intern_atom(display: *mut xlib::Display, name: &str) -> xlib::Atom {
+    let name = CString::new(name).expect("atom names must not contain 0 bytes");
+    xlib::XInternAtom(display, name.as_ptr(), xlib::False)
+}
+
+unsafe fn atom_name(display: *mut xlib::Display, atom: xlib::Atom) -> String {
+    let name = xlib::XGetAtomName(display, atom);
+    if name.is_null() {
+        return String::new();
+    }
+    let result = CStr::from_ptr(name).to_string_lossy().into_owned();
+    xlib::XFree(name as *mut c_void);
+    result
+}
+
+/// Reads an integer setting from the data of an `_XSETTINGS_SETTINGS` property
+fn xsettings_int(data: &[u8], name: &str) -> Option<i32> {
+    // byte order as in X11: 0 is LSBFirst, 1 is MSBFirst
+    let big_endian = *data.first()? == 1;
+    let read_u16 = |pos: usize| -> Option<u16> {
+        let b = data.get(pos..pos + 2)?;
+        Some(if big_endian { u16::from_be_bytes([b[0], b[1]]) } else { u16::from_le_bytes([b[0], b[1]]) })
+    };
+    let read_u32 = |pos: usize| -> Option<u32> {
+        let b = data.get(pos..pos + 4)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Some(if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    };
+    let padded = |len: usize| (len + 3) & !3;
+
+    let num_settings = read_u32(8)?;
+    let mut pos = 12;
+    for _ in 0..num_settings {
+        let setting_type = *data.get(pos)?;
+        let name_len = read_u16(pos + 2)? as usize;
+        let setting_name = data.get(pos + 4..pos + 4 + name_len)?;
+        // skip header, name and serial of last change
+        pos += 4 + padded(name_len) + 4;
+        match setting_type {
+            0 if setting_name == name.as_bytes() => return Some(read_u32(pos)? as i32),
+            0 => pos += 4,
+            1 => pos += 4 + padded(read_u32(pos)? as usize),
+            2 => pos += 8,
+            _ => return None
+        }
+    }
+    None
+}
+
+/// Reads the double click time in seconds and the double click
+/// distance from the XSETTINGS of the desktop environment
+///
+/// Returns `None` for settings that are not available.
+pub(crate) unsafe fn double_click_settings(display: *mut c_void) -> (Option<f64>, Option<f64>) {
+    let display = display as *mut xlib::Display;
+    if display.is_null() {
+        return (None, None);
+    }
+
+    let selection = intern_atom(display, &format!("_XSETTINGS_S{}", xlib::XDefaultScreen(display)));
+    let owner = xlib::XGetSelectionOwner(display, selection);
+    if owner == 0 {
+        return (None, None);
+    }
+
+    let settings = intern_atom(display, "_XSETTINGS_SETTINGS");
+    let mut actual_type = 0;
+    let mut actual_format = 0;
+    let mut num_items = 0;
+    let mut bytes_after = 0;
+    let mut data: *mut u8 = std::ptr::null_mut();
+    let status = xlib::XGetWindowProperty(
+        display, owner, settings,
+        0, c_long::MAX, xlib::False, settings,
+        &mut actual_type, &mut actual_format, &mut num_items, &mut bytes_after, &mut data);
+    if status != xlib::Success as c_int || data.is_null() {
+        return (None, None);
+    }
+    let bytes = std::slice::from_raw_parts(data, num_items as usize).to_vec();
+    xlib::XFree(data as *mut c_void);
+
+    (xsettings_int(&bytes, "Net/DoubleClickTime").map(|ms| ms as f64 / 1000.),
+     xsettings_int(&bytes, "Net/DoubleClickDistance").map(|px| px as f64))
+}
+
+/// Makes the X server report key repeats as key presses only
+///
+/// By default, X11 reports a key repeat as a release followed by a
+/// press of the key, which cannot be told apart from the user
+/// releasing and pressing the key again.
+pub(crate) unsafe fn set_detectable_auto_repeat(display: *mut c_void) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() {
+        return Status::Failure;
+    }
+
+    let mut supported = xlib::False;
+    xlib::XkbSetDetectableAutoRepeat(display, xlib::True, &mut supported);
+
+    match supported {
+        xlib::False => Status::Failure,
+        _ => Status::Success
+    }
+}
+
+/// Queries the active monitors of the default screen using Xrandr
+pub(crate) unsafe fn monitors(display: *mut c_void) -> Vec<Monitor> {
+    let display = display as *mut xlib::Display;
+    if display.is_null() {
+        return Vec::new();
+    }
+
+    let mut count = 0;
+    let infos = xrandr::XRRGetMonitors(display, xlib::XDefaultRootWindow(display), xlib::True, &mut count);
+    if infos.is_null() {
+        return Vec::new();
+    }
+
+    let monitors = std::slice::from_raw_parts(infos, count as usize).iter()
+        .map(|info| Monitor {
+            name: atom_name(display, info.name),
+            geometry: Rect {
+                pos: Coord { x: info.x as f64, y: info.y as f64 },
+                size: Size { w: info.width as f64, h: info.height as f64 }
+            },
+            primary: info.primary != 0
+        })
+        .collect();
+    xrandr::XRRFreeMonitors(infos);
+
+    monitors
+}
+
+/// Sets the `_NET_WM_ICON` property of `window` to `icons`
+///
+/// An empty `icons` slice removes the property.
+pub(crate) unsafe fn set_window_icon(display: *mut c_void, window: p::PuglNativeView, icons: &[WindowIcon]) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() || window == 0 {
+        return Status::Failure;
+    }
+
+    let net_wm_icon = intern_atom(display, "_NET_WM_ICON");
+
+    if icons.is_empty() {
+        xlib::XDeleteProperty(display, window as xlib::Window, net_wm_icon);
+    } else {
+        // Format 32 properties are passed as an array of C longs
+        let mut data: Vec<c_ulong> = Vec::new();
+        for icon in icons {
+            data.push(icon.width() as c_ulong);
+            data.push(icon.height() as c_ulong);
+            data.extend(icon.pixels().iter().map(|&px| px as c_ulong));
+        }
+        xlib::XChangeProperty(
+            display, window as xlib::Window,
+            net_wm_icon, xlib::XA_CARDINAL, 32, xlib::PropModeReplace,
+            data.as_ptr() as *const u8, data.len() as c_int);
+    }
+    xlib::XFlush(display);
+
+    Status::Success
+}
+
+/// Grabs the pointer so that all pointer events are reported to `window`
+pub(crate) unsafe fn grab_pointer(display: *mut c_void, window: p::PuglNativeView) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() || window == 0 {
+        return Status::Failure;
+    }
+
+    let mask = xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::PointerMotionMask;
+    let result = xlib::XGrabPointer(
+        display, window as xlib::Window, xlib::False, mask as u32,
+        xlib::GrabModeAsync, xlib::GrabModeAsync, 0, 0, xlib::CurrentTime);
+    xlib::XFlush(display);
+
+    match result {
+        xlib::GrabSuccess => Status::Success,
+        _ => Status::Failure
+    }
+}
+
+/// Releases a pointer grab
+pub(crate) unsafe fn ungrab_pointer(display: *mut c_void) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() {
+        return Status::Failure;
+    }
+
+    xlib::XUngrabPointer(display, xlib::CurrentTime);
+    xlib::XFlush(display);
+
+    Status::Success
+}
+
+/// Moves the pointer to `pos` relative to `window`
+pub(crate) unsafe fn warp_pointer(display: *mut c_void, window: p::PuglNativeView, pos: Coord) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() || window == 0 {
+        return Status::Failure;
+    }
+
+    xlib::XWarpPointer(
+        display, 0, window as xlib::Window,
+        0, 0, 0, 0, pos.x.round() as c_int, pos.y.round() as c_int);
+    xlib::XFlush(display);
+
+    Status::Success
+}
+
+/// Hides or shows the cursor while the pointer is inside `window`
+///
+/// This does not change the cursor defined for the window.
+pub(crate) unsafe fn set_cursor_visible(display: *mut c_void, window: p::PuglNativeView, visible: bool) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() || window == 0 {
+        return Status::Failure;
+    }
+
+    match visible {
+        true => xfixes::XFixesShowCursor(display, window as xlib::Window),
+        false => xfixes::XFixesHideCursor(display, window as xlib::Window)
+    }
+    xlib::XFlush(display);
+
+    Status::Success
+}
+
+// from Xutil.h
+const NORMAL_STATE: c_int = 1;
+const ICONIC_STATE: c_int = 3;
+
+const NET_WM_STATE_REMOVE: c_long = 0;
+const NET_WM_STATE_ADD: c_long = 1;
+
+fn window_state_atom_names(state: WindowState) -> &'static [&'static str] {
+    match state {
+        WindowState::MAXIMIZED => &["_NET_WM_STATE_MAXIMIZED_VERT", "_NET_WM_STATE_MAXIMIZED_HORZ"],
+        WindowState::MINIMIZED => &["_NET_WM_STATE_HIDDEN"],
+        WindowState::FULLSCREEN => &["_NET_WM_STATE_FULLSCREEN"],
+        WindowState::ABOVE => &["_NET_WM_STATE_ABOVE"],
+        WindowState::BELOW => &["_NET_WM_STATE_BELOW"],
+        _ => &[]
+    }
+}
+
+const WINDOW_STATES: [WindowState; 5] = [
+    WindowState::MAXIMIZED,
+    WindowState::MINIMIZED,
+    WindowState::FULLSCREEN,
+    WindowState::ABOVE,
+    WindowState::BELOW
+];
+
+unsafe fn net_wm_state_atoms(display: *mut xlib::Display, window: xlib::Window) -> Vec<xlib::Atom> {
+    let mut actual_type = 0;
+    let mut actual_format = 0;
+    let mut num_items = 0;
+    let mut bytes_after = 0;
+    let mut data: *mut u8 = std::ptr::null_mut();
+
+    let status = xlib::XGetWindowProperty(
+        display, window, intern_atom(display, "_NET_WM_STATE"),
+        0, c_long::MAX, xlib::False, xlib::XA_ATOM,
+        &mut actual_type, &mut actual_format, &mut num_items, &mut bytes_after, &mut data);
+
+    if status != xlib::Success as c_int || data.is_null() {
+        return Vec::new();
+    }
+    let atoms = std::slice::from_raw_parts(data as *const xlib::Atom, num_items as usize).to_vec();
+    xlib::XFree(data as *mut c_void);
+    atoms
+}
+
+unsafe fn is_mapped(display: *mut xlib::Display, window: xlib::Window) -> bool {
+    let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+    xlib::XGetWindowAttributes(display, window, &mut attributes) != 0
+        && attributes.map_state != xlib::IsUnmapped
+}
+
+/// Reads the state of `window` from its `_NET_WM_STATE` property
+pub(crate) unsafe fn window_state(display: *mut c_void, window: p::PuglNativeView) -> WindowState {
+    let display = display as *mut xlib::Display;
+    if display.is_null() || window == 0 {
+        return WindowState::NONE;
+    }
+    let atoms = net_wm_state_atoms(display, window as xlib::Window);
+    WINDOW_STATES.iter()
+        .filter(|&&state| window_state_atom_names(state).iter()
+                .all(|name| atoms.contains(&intern_atom(display, name))))
+        .fold(WindowState::NONE, |acc, &state| acc | state)
+}
+
+/// Watches the `_NET_WM_STATE` property of a window
+///
+/// pugl does not report property changes and consumes all the events
+/// of its connection to the X server. So the watcher selects the
+/// `PropertyNotify` events of the window on a connection of its own.
+/// The connection is only polled after pugl's event loop returned, so
+/// changes do not wake up the loop.
+pub(crate) struct WindowStateWatcher {
+    display: *mut xlib::Display,
+    net_wm_state: xlib::Atom
+}
+
+impl WindowStateWatcher {
+    /// Starts watching `window`, `None` if no connection to the X server can be opened
+    pub(crate) unsafe fn new(display: *mut c_void, window: p::PuglNativeView) -> Option<WindowStateWatcher> {
+        let display = display as *mut xlib::Display;
+        if display.is_null() || window == 0 {
+            return None;
+        }
+        let own_display = xlib::XOpenDisplay(xlib::XDisplayString(display));
+        if own_display.is_null() {
+            return None;
+        }
+        xlib::XSelectInput(own_display, window as xlib::Window, xlib::PropertyChangeMask);
+        xlib::XFlush(own_display);
+        Some(WindowStateWatcher { display: own_display, net_wm_state: intern_atom(own_display, "_NET_WM_STATE") })
+    }
+
+    /// Returns `true` if `_NET_WM_STATE` has changed since the last call
+    pub(crate) fn take_change(&mut self) -> bool {
+        let mut changed = false;
+        unsafe {
+            while xlib::XPending(self.display) > 0 {
+                let mut event: xlib::XEvent = std::mem::zeroed();
+                xlib::XNextEvent(self.display, &mut event);
+                if event.get_type() == xlib::PropertyNotify && event.property.atom == self.net_wm_state {
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+}
+
+impl Drop for WindowStateWatcher {
+    fn drop(&mut self) {
+        unsafe { xlib::XCloseDisplay(self.display); }
+    }
+}
+
+/// Requests the window manager to add or remove `state` to `window`
+///
+/// As long as `window` is not mapped the `_NET_WM_STATE` property is
+/// set directly, afterwards the request is sent to the root window as
+/// specified by EWMH.
+pub(crate) unsafe fn set_window_state(display: *mut c_void, window: p::PuglNativeView, state: WindowState, enable: bool) -> Status {
+    let display = display as *mut xlib::Display;
+    if display.is_null() || window == 0 {
+        return Status::Failure;
+    }
+    let window = window as xlib::Window;
+
+    if is_mapped(display, window) {
+        set_mapped_window_state(display, window, state, enable);
+    } else {
+        set_unmapped_window_state(display, window, state, enable);
+    }
+    xlib::XFlush(display);
+
+    Status::Success
+}
+
+unsafe fn set_mapped_window_state(display: *mut xlib::Display, window: xlib::Window, state: WindowState, enable: bool) {
+    if state.contains(WindowState::MINIMIZED) {
+        if enable {
+            xlib::XIconifyWindow(display, window, xlib::XDefaultScreen(display));
+        } else {
+            xlib::XMapRaised(display, window);
+        }
+    }
+
+    let net_wm_state = intern_atom(display, "_NET_WM_STATE");
+    let action = if enable { NET_WM_STATE_ADD } else { NET_WM_STATE_REMOVE };
+
+    for &st in WINDOW_STATES.iter().filter(|&&st| st != WindowState::MINIMIZED && state.contains(st)) {
+        let names = window_state_atom_names(st);
+
+        let mut event: xlib::XClientMessageEvent = std::mem::zeroed();
+        event.type_ = xlib::ClientMessage;
+        event.window = window;
+        event.message_type = net_wm_state;
+        event.format = 32;
+        event.data.set_long(0, action);
+        for (i, name) in names.iter().enumerate() {
+            event.data.set_long(i + 1, intern_atom(display, name) as c_long);
+        }
+        // source indication: normal application
+        event.data.set_long(3, 1);
+
+        let mut event = xlib::XEvent::from(event);
+        xlib::XSendEvent(
+            display, xlib::XDefaultRootWindow(display), xlib::False,
+            xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+            &mut event);
+    }
+}
+
+unsafe fn set_unmapped_window_state(display: *mut xlib::Display, window: xlib::Window, state: WindowState, enable: bool) {
+    if state.contains(WindowState::MINIMIZED) {
+        // keep the hints pugl or the application have set already
+        let mut hints = xlib::XGetWMHints(display, window);
+        if hints.is_null() {
+            hints = xlib::XAllocWMHints();
+        }
+        if !hints.is_null() {
+            (*hints).flags |= xlib::StateHint;
+            (*hints).initial_state = if enable { ICONIC_STATE } else { NORMAL_STATE };
+            xlib::XSetWMHints(display, window, hints);
+            xlib::XFree(hints as *mut c_void);
+        }
+    }
+
+    let mut atoms = net_wm_state_atoms(display, window);
+    for &st in WINDOW_STATES.iter().filter(|&&st| st != WindowState::MINIMIZED && state.contains(st)) {
+        for name in window_state_atom_names(st) {
+            let atom = intern_atom(display, name);
+            atoms.retain(|&a| a != atom);
+            if enable {
+                atoms.push(atom);
+            }
+        }
+    }
+
+    xlib::XChangeProperty(
+        display, window,
+        intern_atom(display, "_NET_WM_STATE"), xlib::XA_ATOM, 32, xlib::PropModeReplace,
+        atoms.as_ptr() as *const u8, atoms.len() as c_int);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn xsettings_data() -> Vec<u8> {
+        let mut data = vec![0, 0, 0, 0,  1, 0, 0, 0,  3, 0, 0, 0];
+        // string setting "Net/ThemeName" = "Foo"
+        data.extend(&[1, 0, 13, 0]);
+        data.extend(b"Net/ThemeName\0\0\0");
+        data.extend(&[0, 0, 0, 0,  3, 0, 0, 0]);
+        data.extend(b"Foo\0");
+        // color setting "Gtk/Color"
+        data.extend(&[2, 0, 9, 0]);
+        data.extend(b"Gtk/Color\0\0\0");
+        data.extend(&[0, 0, 0, 0,  1, 2, 3, 4, 5, 6, 7, 8]);
+        // int setting "Net/DoubleClickTime" = 250
+        data.extend(&[0, 0, 19, 0]);
+        data.extend(b"Net/DoubleClickTime\0");
+        data.extend(&[0, 0, 0, 0,  250, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn xsettings_int_found() {
+        assert_eq!(xsettings_int(&xsettings_data(), "Net/DoubleClickTime"), Some(250));
+    }
+
+    #[test]
+    fn xsettings_int_not_found() {
+        assert_eq!(xsettings_int(&xsettings_data(), "Net/DoubleClickDistance"), None);
+    }
+
+    #[test]
+    fn xsettings_int_truncated() {
+        let data = xsettings_data();
+        assert_eq!(xsettings_int(&data[..data.len() - 2], "Net/DoubleClickTime"), None);
+        assert_eq!(xsettings_int(&[], "Net/DoubleClickTime"), None);
+    }
+}
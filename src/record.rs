@@ -0,0 +1,260 @@
+//! Recording of the events of a view to reproduce a session
+//!
+//! With the feature `serde`, a recording is written as JSON Lines,
+//! i.e. one [`Record`](struct.Record.html) serialized as JSON per line,
+//! so a recording stays readable if the program does not stop cleanly.
+
+#[cfg(feature = "serde")]
+use std::io;
+
+use crate::types::*;
+use crate::backend::ViewEvent;
+
+/// An event of a view that is recorded
+///
+/// Input events are recorded as they come from the window system,
+/// i.e. before click counts, pressed buttons, key repeats and scroll
+/// phases are determined. So they are processed again when replayed.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedEvent {
+    /// An input event
+    Input(Event),
+    /// The view has been resized
+    Configure(Size),
+    /// The view has received the focus
+    FocusIn,
+    /// The view has lost the focus
+    FocusOut,
+    /// The window system has requested to close the view
+    Close,
+    /// The timer with the given id has fired
+    Timer(usize)
+}
+
+impl RecordedEvent {
+    /// Returns the recorded form of `event`, `None` for exposes which are not recorded
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_view_event(event: &ViewEvent) -> Option<RecordedEvent> {
+        Some(match *event {
+            ViewEvent::Input(ev) => RecordedEvent::Input(ev),
+            ViewEvent::Configure(size) => RecordedEvent::Configure(size),
+            ViewEvent::FocusIn => RecordedEvent::FocusIn,
+            ViewEvent::FocusOut => RecordedEvent::FocusOut,
+            ViewEvent::Close => RecordedEvent::Close,
+            ViewEvent::Timer(id) => RecordedEvent::Timer(id),
+            ViewEvent::Expose(..) | ViewEvent::WindowStateChanged | ViewEvent::UpdateDone => return None
+        })
+    }
+
+    pub(crate) fn to_view_event(self) -> ViewEvent {
+        match self {
+            RecordedEvent::Input(ev) => ViewEvent::Input(ev),
+            RecordedEvent::Configure(size) => ViewEvent::Configure(size),
+            RecordedEvent::FocusIn => ViewEvent::FocusIn,
+            RecordedEvent::FocusOut => ViewEvent::FocusOut,
+            RecordedEvent::Close => ViewEvent::Close,
+            RecordedEvent::Timer(id) => ViewEvent::Timer(id)
+        }
+    }
+}
+
+/// A recorded event with the time it happened in seconds
+///
+/// The time is the one of the backend when the event is dispatched,
+/// for input events it differs from the time of the
+/// [`EventContext`](struct.EventContext.html), which is kept as well.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    /// The time of the event in seconds
+    pub time: f64,
+    /// The event
+    pub event: RecordedEvent
+}
+
+/// Reads a recording written by
+/// [`PuglView::start_recording()`](struct.PuglView.html#method.start_recording)
+///
+/// Empty lines are skipped. Lines that cannot be deserialized result
+/// in an error of the kind `InvalidData` telling the line number.
+///
+/// ```
+/// use pugl_sys::*;
+///
+/// let recording = "{\"time\":1.5,\"event\":{\"Configure\":{\"w\":320.0,\"h\":200.0}}}\n";
+/// let records = read_recording(recording.as_bytes()).unwrap();
+/// assert_eq!(records, [Record { time: 1.5, event: RecordedEvent::Configure(Size { w: 320., h: 200. }) }]);
+/// ```
+#[cfg(feature = "serde")]
+pub fn read_recording<R: io::BufRead>(reader: R) -> io::Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, e))
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Writes the records of a view
+///
+/// Errors while recording must not disturb the view, so the first
+/// one is kept to be reported when the recording is stopped.
+#[cfg(feature = "serde")]
+pub(crate) struct Recorder {
+    writer: Box<dyn io::Write>,
+    error: Option<io::Error>
+}
+
+#[cfg(feature = "serde")]
+impl Recorder {
+    pub(crate) fn new(writer: Box<dyn io::Write>) -> Recorder {
+        Recorder { writer, error: None }
+    }
+
+    pub(crate) fn record(&mut self, record: &Record) {
+        if self.error.is_none() {
+            self.error = self.write(record).err();
+        }
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.writer.flush()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    fn input(data: EventType) -> Record {
+        let context = EventContext {
+            pos: Coord { x: 12.5, y: 3. },
+            pos_root: Coord { x: 112.5, y: 203. },
+            time: 0.25,
+            flags: EventFlags::IS_SEND_EVENT
+        };
+        Record { time: 0.25, event: RecordedEvent::Input(Event { data, context }) }
+    }
+
+    fn roundtrip(record: Record) {
+        let line = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<Record>(&line).unwrap(), record, "{}", line);
+    }
+
+    #[test]
+    fn roundtrip_records() {
+        let key = |key| Key { key, modifiers: Modifiers::CTRL | Modifiers::SHIFT, code: 52, is_repeat: true };
+        let button = MouseButton { button: Button::Other(12), modifiers: Modifiers::ALT, click_count: 2 };
+        let records = [
+            Record { time: 0.1, event: RecordedEvent::Configure(Size { w: 320.5, h: 200. }) },
+            Record { time: 0., event: RecordedEvent::FocusIn },
+            Record { time: 1e-3, event: RecordedEvent::FocusOut },
+            Record { time: 2., event: RecordedEvent::Close },
+            Record { time: 3.75, event: RecordedEvent::Timer(42) },
+            input(EventType::KeyPress(key(KeyVal::Character(' ')))),
+            input(EventType::KeyRelease(key(KeyVal::Character('ä')))),
+            input(EventType::KeyPress(key(KeyVal::Special(SpecialKey::MediaNext)))),
+            input(EventType::KeyPress(key(KeyVal::Unknown(0xd800)))),
+            input(EventType::MouseButtonPress(button)),
+            input(EventType::MouseButtonRelease(MouseButton { button: Button::Left, ..button })),
+            input(EventType::MouseMove(MotionContext {
+                modifiers: Modifiers::SUPER,
+                buttons: MouseButtons::LEFT | MouseButtons::RIGHT,
+                flags: EventFlags::IS_HINT
+            })),
+            input(EventType::RelativeMove(RelativeMotion {
+                delta: Coord { x: -1.5, y: 2. },
+                modifiers: Modifiers::NONE,
+                buttons: MouseButtons::MIDDLE
+            })),
+            input(EventType::PointerIn(CrossingMode::Ungrab)),
+            input(EventType::PointerOut(CrossingMode::Grab)),
+            input(EventType::Scroll(Scroll {
+                dx: 0.5, dy: -0.25,
+                modifiers: Modifiers::CTRL,
+                direction: ScrollDirection::Smooth,
+                phase: ScrollPhase::Update
+            })),
+        ];
+        for &record in records.iter() {
+            roundtrip(record);
+        }
+    }
+
+    #[test]
+    fn read_and_write_recording() {
+        let records = vec![
+            Record { time: 1., event: RecordedEvent::FocusIn },
+            Record { time: 1.5, event: RecordedEvent::Timer(1) },
+        ];
+        let written = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        struct Shared(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+        impl io::Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.borrow_mut().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> { Ok(()) }
+        }
+
+        let mut recorder = Recorder::new(Box::new(Shared(written.clone())));
+        for record in records.iter() {
+            recorder.record(record);
+        }
+        recorder.finish().unwrap();
+
+        let text = String::from_utf8(written.borrow().clone()).unwrap();
+        assert_eq!(text, "{\"time\":1.0,\"event\":\"FocusIn\"}\n{\"time\":1.5,\"event\":{\"Timer\":1}}\n");
+        assert_eq!(read_recording(text.as_bytes()).unwrap(), records);
+        assert_eq!(read_recording(format!("\n{}\n", text).as_bytes()).unwrap(), records);
+    }
+
+    #[test]
+    fn read_errors_tell_the_line() {
+        let error = read_recording("{\"time\":1.0,\"event\":\"FocusIn\"}\n{\"time\":1.0,\"event\":\"Resize\"}\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("line 2: unknown variant `Resize`"), "{}", error);
+    }
+
+    #[test]
+    fn first_write_error_reported() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        struct Failing(Rc<Cell<bool>>);
+        impl io::Write for Failing {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                match self.0.get() {
+                    true => Err(io::Error::new(io::ErrorKind::WriteZero, "disk full")),
+                    false => Ok(buf.len())
+                }
+            }
+            fn flush(&mut self) -> io::Result<()> { Ok(()) }
+        }
+
+        let failing = Rc::new(Cell::new(false));
+        let mut recorder = Recorder::new(Box::new(Failing(failing.clone())));
+        failing.set(true);
+        recorder.record(&Record { time: 1., event: RecordedEvent::Close });
+        failing.set(false);
+        recorder.record(&Record { time: 2., event: RecordedEvent::Close });
+        assert_eq!(recorder.finish().unwrap_err().to_string(), "disk full");
+    }
+}
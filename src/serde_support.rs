@@ -0,0 +1,183 @@
+//! `Serialize` and `Deserialize` for the types that cannot derive them
+//!
+//! Flags are represented by the list of the names of the set flags,
+//! shortcuts by their string like "Ctrl+Z".
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::types::*;
+use crate::shortcut::Shortcut;
+
+macro_rules! serde_flags {
+    ($flags:ident, $kind:expr, [$(($flag:ident, $name:expr)),* $(,)?]) => {
+        impl $flags {
+            const SERDE_NAMES: &'static [($flags, &'static str)] = &[$(($flags::$flag, $name)),*];
+        }
+
+        impl Serialize for $flags {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let names = $flags::SERDE_NAMES.iter().filter(|&&(flag, _)| self.contains(flag));
+                let mut seq = serializer.serialize_seq(Some(names.clone().count()))?;
+                for (_, name) in names {
+                    seq.serialize_element(name)?;
+                }
+                seq.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $flags {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$flags, D::Error> {
+                deserializer.deserialize_seq(FlagsVisitor { names: $flags::SERDE_NAMES, flag: $kind })
+            }
+        }
+    }
+}
+
+struct FlagsVisitor<F: 'static> {
+    names: &'static [(F, &'static str)],
+    flag: &'static str
+}
+
+impl<'de, F> Visitor<'de> for FlagsVisitor<F>
+where F: Copy + Default + std::ops::BitOr<Output = F> {
+    type Value = F;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of {} names", self.flag)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<F, A::Error> {
+        let mut flags = F::default();
+        while let Some(name) = seq.next_element::<String>()? {
+            let &(flag, _) = self.names.iter()
+                .find(|&&(_, n)| n == name)
+                .ok_or_else(|| de::Error::custom(format_args!("unknown {} \"{}\"", self.flag, name)))?;
+            flags = flags | flag;
+        }
+        Ok(flags)
+    }
+}
+
+serde_flags!(Modifiers, "modifier", [
+    (SHIFT, "Shift"),
+    (CTRL, "Ctrl"),
+    (ALT, "Alt"),
+    (SUPER, "Super"),
+]);
+
+serde_flags!(MouseButtons, "mouse button", [
+    (LEFT, "Left"),
+    (MIDDLE, "Middle"),
+    (RIGHT, "Right"),
+    (BACK, "Back"),
+    (FORWARD, "Forward"),
+]);
+
+serde_flags!(EventFlags, "event flag", [
+    (IS_SEND_EVENT, "IsSendEvent"),
+    (IS_HINT, "IsHint"),
+]);
+
+serde_flags!(WindowState, "window state", [
+    (MAXIMIZED, "Maximized"),
+    (MINIMIZED, "Minimized"),
+    (FULLSCREEN, "Fullscreen"),
+    (ABOVE, "Above"),
+    (BELOW, "Below"),
+]);
+
+impl Serialize for Shortcut {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Shortcut {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Shortcut, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::record::{Record, RecordedEvent};
+
+    fn roundtrip<T>(value: T, json: &str)
+    where T: Serialize + for<'de> Deserialize<'de> + PartialEq + fmt::Debug {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn geometry() {
+        roundtrip(Coord { x: 1.5, y: -2. }, r#"{"x":1.5,"y":-2.0}"#);
+        roundtrip(
+            Rect { pos: Coord { x: 0., y: 1. }, size: Size { w: 320., h: 200. } },
+            r#"{"pos":{"x":0.0,"y":1.0},"size":{"w":320.0,"h":200.0}}"#
+        );
+        roundtrip(ExposeArea { pos: Coord::default(), size: Size { w: 2., h: 3. } }, r#"{"pos":{"x":0.0,"y":0.0},"size":{"w":2.0,"h":3.0}}"#);
+        roundtrip(Cursor::Hand, r#""Hand""#);
+    }
+
+    #[test]
+    fn flags() {
+        roundtrip(Modifiers::NONE, "[]");
+        roundtrip(Modifiers::CTRL | Modifiers::SHIFT, r#"["Shift","Ctrl"]"#);
+        roundtrip(MouseButtons::LEFT | MouseButtons::FORWARD, r#"["Left","Forward"]"#);
+        roundtrip(EventFlags::IS_HINT, r#"["IsHint"]"#);
+        roundtrip(WindowState::MAXIMIZED | WindowState::ABOVE, r#"["Maximized","Above"]"#);
+        assert_eq!(serde_json::from_str::<Modifiers>(r#"["Alt","Ctrl"]"#).unwrap(), Modifiers::ALT | Modifiers::CTRL);
+
+        let error = serde_json::from_str::<Modifiers>(r#"["Hyper"]"#).unwrap_err();
+        assert!(error.to_string().starts_with(r#"unknown modifier "Hyper""#), "{}", error);
+    }
+
+    #[test]
+    fn keys() {
+        roundtrip(KeyVal::Character('a'), r#"{"Character":"a"}"#);
+        roundtrip(KeyVal::Special(SpecialKey::F1), r#"{"Special":"F1"}"#);
+        roundtrip(
+            Key { key: KeyVal::Character('z'), modifiers: Modifiers::CTRL, code: 52, is_repeat: false },
+            r#"{"key":{"Character":"z"},"modifiers":["Ctrl"],"code":52,"is_repeat":false}"#
+        );
+        roundtrip("Ctrl+Shift+Z".parse::<Shortcut>().unwrap(), r#""Ctrl+Shift+Z""#);
+        assert!(serde_json::from_str::<Shortcut>(r#""Hyper+Z""#).is_err());
+    }
+
+    #[test]
+    fn special_key_shortcuts() {
+        for &sk in SpecialKey::ALL {
+            let shortcut = Shortcut::new(KeyVal::Special(sk), Modifiers::CTRL);
+            let json = serde_json::to_string(&shortcut).unwrap();
+            assert_eq!(serde_json::from_str::<Shortcut>(&json).unwrap(), shortcut, "{}", json);
+        }
+    }
+
+    #[test]
+    fn events() {
+        let event = Event {
+            data: EventType::MouseButtonPress(MouseButton { button: Button::Right, modifiers: Modifiers::SHIFT, click_count: 2 }),
+            context: EventContext { pos: Coord { x: 1., y: 2. }, pos_root: Coord { x: 3., y: 4. }, time: 0.5, flags: EventFlags::NONE }
+        };
+        roundtrip(event, concat!(
+            r#"{"data":{"MouseButtonPress":{"button":"Right","modifiers":["Shift"],"click_count":2}},"#,
+            r#""context":{"pos":{"x":1.0,"y":2.0},"pos_root":{"x":3.0,"y":4.0},"time":0.5,"flags":[]}}"#
+        ));
+        let scroll = Scroll {
+            dx: 0., dy: 1.,
+            modifiers: Modifiers::NONE,
+            direction: ScrollDirection::Up,
+            phase: ScrollPhase::None
+        };
+        roundtrip(
+            EventType::Scroll(scroll),
+            r#"{"Scroll":{"dx":0.0,"dy":1.0,"modifiers":[],"direction":"Up","phase":"None"}}"#
+        );
+        roundtrip(Record { time: 1., event: RecordedEvent::Timer(3) }, r#"{"time":1.0,"event":{"Timer":3}}"#);
+    }
+}
@@ -0,0 +1,483 @@
+//! Keyboard shortcuts
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::*;
+
+const MODIFIER_NAMES: &[(Modifiers, &str)] = &[
+    (Modifiers::CTRL, "Ctrl"),
+    (Modifiers::SHIFT, "Shift"),
+    (Modifiers::ALT, "Alt"),
+    (Modifiers::SUPER, "Super"),
+];
+
+const SPECIAL_KEY_NAMES: &[(SpecialKey, &str)] = &[
+    (SpecialKey::Backspace, "Backspace"),
+    (SpecialKey::Escape, "Escape"),
+    (SpecialKey::Delete, "Delete"),
+    (SpecialKey::F1, "F1"),
+    (SpecialKey::F2, "F2"),
+    (SpecialKey::F3, "F3"),
+    (SpecialKey::F4, "F4"),
+    (SpecialKey::F5, "F5"),
+    (SpecialKey::F6, "F6"),
+    (SpecialKey::F7, "F7"),
+    (SpecialKey::F8, "F8"),
+    (SpecialKey::F9, "F9"),
+    (SpecialKey::F10, "F10"),
+    (SpecialKey::F11, "F11"),
+    (SpecialKey::F12, "F12"),
+    (SpecialKey::Left, "Left"),
+    (SpecialKey::Up, "Up"),
+    (SpecialKey::Right, "Right"),
+    (SpecialKey::Down, "Down"),
+    (SpecialKey::PageUp, "PageUp"),
+    (SpecialKey::PageDown, "PageDown"),
+    (SpecialKey::Home, "Home"),
+    (SpecialKey::End, "End"),
+    (SpecialKey::Insert, "Insert"),
+    (SpecialKey::ShiftL, "ShiftL"),
+    (SpecialKey::ShiftR, "ShiftR"),
+    (SpecialKey::CtrlL, "CtrlL"),
+    (SpecialKey::CtrlR, "CtrlR"),
+    (SpecialKey::AltL, "AltL"),
+    (SpecialKey::AltR, "AltR"),
+    (SpecialKey::SuperL, "SuperL"),
+    (SpecialKey::SuperR, "SuperR"),
+    (SpecialKey::KeyMenu, "Menu"),
+    (SpecialKey::KeyCapsLock, "CapsLock"),
+    (SpecialKey::KeyScrollLock, "ScrollLock"),
+    (SpecialKey::KeyNumLock, "NumLock"),
+    (SpecialKey::KeyPrintScreen, "PrintScreen"),
+    (SpecialKey::KeyPause, "Pause"),
+    (SpecialKey::None, "None"),
+    (SpecialKey::Keypad0, "Keypad0"),
+    (SpecialKey::Keypad1, "Keypad1"),
+    (SpecialKey::Keypad2, "Keypad2"),
+    (SpecialKey::Keypad3, "Keypad3"),
+    (SpecialKey::Keypad4, "Keypad4"),
+    (SpecialKey::Keypad5, "Keypad5"),
+    (SpecialKey::Keypad6, "Keypad6"),
+    (SpecialKey::Keypad7, "Keypad7"),
+    (SpecialKey::Keypad8, "Keypad8"),
+    (SpecialKey::Keypad9, "Keypad9"),
+    (SpecialKey::KeypadDecimal, "KeypadDecimal"),
+    (SpecialKey::KeypadEnter, "KeypadEnter"),
+    (SpecialKey::KeypadAdd, "KeypadAdd"),
+    (SpecialKey::KeypadSubtract, "KeypadSubtract"),
+    (SpecialKey::KeypadMultiply, "KeypadMultiply"),
+    (SpecialKey::KeypadDivide, "KeypadDivide"),
+    (SpecialKey::VolumeMute, "VolumeMute"),
+    (SpecialKey::VolumeDown, "VolumeDown"),
+    (SpecialKey::VolumeUp, "VolumeUp"),
+    (SpecialKey::MediaPlayPause, "MediaPlayPause"),
+    (SpecialKey::MediaStop, "MediaStop"),
+    (SpecialKey::MediaPrevious, "MediaPrevious"),
+    (SpecialKey::MediaNext, "MediaNext"),
+];
+
+const CHARACTER_NAMES: &[(char, &str)] = &[
+    (' ', "Space"),
+    ('\t', "Tab"),
+    ('\r', "Return"),
+    ('+', "Plus"),
+];
+
+const ALIASES: &[(&str, &str)] = &[
+    ("Control", "Ctrl"),
+    ("Esc", "Escape"),
+    ("Del", "Delete"),
+    ("Enter", "Return"),
+];
+
+/// A keyboard shortcut like "Ctrl+Shift+Z"
+///
+/// Character keys are case insensitive, a shortcut for an upper case
+/// letter needs the `Shift` modifier. So "Ctrl+Z" and "Ctrl+z" are the
+/// same shortcut, which is triggered by Ctrl and Z, but not by Ctrl,
+/// Shift and Z.
+///
+/// As the keyboard layout is not taken into account, shortcuts for
+/// characters that need Shift on a keyboard should be given by the
+/// unshifted key, e.g. "Ctrl+Shift+1" rather than "Ctrl+!" on a US
+/// keyboard.
+///
+/// ```
+/// use pugl_sys::*;
+///
+/// let redo: Shortcut = "Ctrl+Shift+Z".parse().unwrap();
+/// assert_eq!(redo, Shortcut::new(KeyVal::Character('z'), Modifiers::CTRL | Modifiers::SHIFT));
+/// assert_eq!(redo.to_string(), "Ctrl+Shift+Z");
+///
+/// let key = Key { key: KeyVal::Character('z'), modifiers: Modifiers::CTRL | Modifiers::SHIFT, code: 52, is_repeat: false };
+/// assert!(redo.matches(&key));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Shortcut {
+    key: KeyVal,
+    modifiers: Modifiers
+}
+
+impl Shortcut {
+    /// Creates a new `Shortcut` for `key` pressed with `modifiers`
+    ///
+    /// An upper case character implies the `Shift` modifier.
+    pub fn new(key: KeyVal, modifiers: Modifiers) -> Shortcut {
+        match key {
+            KeyVal::Character(c) if c.is_uppercase() => Shortcut {
+                key: KeyVal::Character(to_lowercase(c)),
+                modifiers: modifiers | Modifiers::SHIFT
+            },
+            _ => Shortcut { key, modifiers }
+        }
+    }
+
+    /// The key of the shortcut, lower case for character keys
+    pub fn key(&self) -> KeyVal {
+        self.key
+    }
+
+    /// The modifiers of the shortcut
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Returns `true` if `key` triggers the shortcut
+    ///
+    /// Key repeats are matched as well, so callers need to check
+    /// [`Key::is_repeat`](struct.Key.html#structfield.is_repeat) if
+    /// they should be ignored.
+    pub fn matches(&self, key: &Key) -> bool {
+        *self == Shortcut::from(key)
+    }
+}
+
+impl From<&Key> for Shortcut {
+    fn from(key: &Key) -> Shortcut {
+        Shortcut::new(key.key, key.modifiers)
+    }
+}
+
+fn to_lowercase(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c
+    }
+}
+
+fn to_uppercase(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(u), None) if to_lowercase(u) == c => u,
+        _ => c
+    }
+}
+
+/// Parses the value of a name like "Unknown(0x1234)"
+fn code_from_name(prefix: &str, name: &str) -> Option<u32> {
+    if name.len() <= prefix.len() || !name.is_char_boundary(prefix.len()) {
+        return None;
+    }
+    let (head, value) = name.split_at(prefix.len());
+    if !head.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    let value = value.strip_prefix("0x")?.strip_suffix(')')?;
+    u32::from_str_radix(value, 16).ok()
+}
+
+fn modifier_from_name(name: &str) -> Option<Modifiers> {
+    MODIFIER_NAMES.iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|&(m, _)| m)
+}
+
+fn key_from_name(name: &str) -> Option<KeyVal> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let c = if c.is_uppercase() { to_lowercase(c) } else { c };
+        return Some(KeyVal::Character(c));
+    }
+    if let Some(&(sk, _)) = SPECIAL_KEY_NAMES.iter().find(|(_, n)| n.eq_ignore_ascii_case(name)) {
+        return Some(KeyVal::Special(sk));
+    }
+    if let Some(k) = code_from_name("Unknown(", name) {
+        return Some(KeyVal::Unknown(k));
+    }
+    if let Some(c) = code_from_name("Char(", name) {
+        return char::from_u32(c).map(KeyVal::Character);
+    }
+    CHARACTER_NAMES.iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|&(c, _)| KeyVal::Character(c))
+}
+
+fn resolve_alias(name: &str) -> &str {
+    ALIASES.iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or(name, |&(_, n)| n)
+}
+
+/// Error parsing a [`Shortcut`](struct.Shortcut.html) from a string
+#[derive(Clone, PartialEq, Debug)]
+pub enum ShortcutParseError {
+    /// The string does not contain a key
+    MissingKey,
+    /// A part before the key is not a modifier
+    UnknownModifier(String),
+    /// The key is neither a character nor a known key name
+    UnknownKey(String)
+}
+
+impl fmt::Display for ShortcutParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShortcutParseError::MissingKey => write!(f, "missing key in shortcut"),
+            ShortcutParseError::UnknownModifier(m) => write!(f, "unknown modifier \"{}\" in shortcut", m),
+            ShortcutParseError::UnknownKey(k) => write!(f, "unknown key \"{}\" in shortcut", k)
+        }
+    }
+}
+
+impl Error for ShortcutParseError {}
+
+impl FromStr for Shortcut {
+    type Err = ShortcutParseError;
+
+    /// Parses a shortcut like "Ctrl+Shift+Z"
+    ///
+    /// Modifiers and key are separated by `+`. Modifier and key names
+    /// are case insensitive. The plus key is given by "Plus" or as a
+    /// trailing `+`, like in "Ctrl++".
+    ///
+    /// Unknown keys and whitespace characters without a name are given
+    /// by their hexadecimal value, like "Unknown(0xd800)" and
+    /// "Char(0xa)", as written by `Display`.
+    fn from_str(s: &str) -> Result<Shortcut, ShortcutParseError> {
+        let s = s.trim();
+        let (modifier_part, key_name) = match s.strip_suffix("++") {
+            Some(rest) => (Some(rest), "+"),
+            None if s == "+" => (None, "+"),
+            None => match s.rfind('+') {
+                Some(pos) => (Some(&s[..pos]), &s[pos+1..]),
+                None => (None, s)
+            }
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        for name in modifier_part.into_iter().flat_map(|m| m.split('+')) {
+            let name = name.trim();
+            modifiers |= modifier_from_name(resolve_alias(name))
+                .ok_or_else(|| ShortcutParseError::UnknownModifier(name.to_string()))?;
+        }
+
+        let key_name = key_name.trim();
+        if key_name.is_empty() {
+            return Err(ShortcutParseError::MissingKey);
+        }
+        let key = key_from_name(resolve_alias(key_name))
+            .ok_or_else(|| ShortcutParseError::UnknownKey(key_name.to_string()))?;
+
+        Ok(Shortcut::new(key, modifiers))
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(m, name) in MODIFIER_NAMES {
+            if self.modifiers.contains(m) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match self.key {
+            KeyVal::Special(sk) => {
+                let &(_, name) = SPECIAL_KEY_NAMES.iter().find(|&&(k, _)| k == sk)
+                    .expect("every special key has a name");
+                write!(f, "{}", name)
+            }
+            KeyVal::Character(c) => match CHARACTER_NAMES.iter().find(|&&(k, _)| k == c) {
+                Some(&(_, name)) => write!(f, "{}", name),
+                // would be trimmed away when parsed
+                None if c.is_whitespace() => write!(f, "Char({:#x})", u32::from(c)),
+                None => write!(f, "{}", to_uppercase(c))
+            },
+            KeyVal::Unknown(k) => write!(f, "Unknown({:#x})", k)
+        }
+    }
+}
+
+/// A map binding [`Shortcut`](struct.Shortcut.html)s to actions
+///
+/// ```
+/// use pugl_sys::*;
+///
+/// #[derive(Copy, Clone, PartialEq, Debug)]
+/// enum Action { Undo, Redo }
+///
+/// let mut map = ShortcutMap::new();
+/// map.insert("Ctrl+Z".parse().unwrap(), Action::Undo);
+/// map.insert("Ctrl+Shift+Z".parse().unwrap(), Action::Redo);
+///
+/// let key = Key { key: KeyVal::Character('z'), modifiers: Modifiers::CTRL, code: 52, is_repeat: false };
+/// assert_eq!(map.lookup(&key), Some(&Action::Undo));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShortcutMap<A> {
+    bindings: HashMap<Shortcut, A>
+}
+
+impl<A> Default for ShortcutMap<A> {
+    fn default() -> Self {
+        ShortcutMap { bindings: HashMap::new() }
+    }
+}
+
+impl<A> ShortcutMap<A> {
+    /// Creates an empty `ShortcutMap`
+    pub fn new() -> ShortcutMap<A> {
+        Default::default()
+    }
+
+    /// Binds `shortcut` to `action`
+    ///
+    /// Returns the action previously bound to `shortcut`, if any.
+    pub fn insert(&mut self, shortcut: Shortcut, action: A) -> Option<A> {
+        self.bindings.insert(shortcut, action)
+    }
+
+    /// Removes the binding of `shortcut` and returns its action
+    pub fn remove(&mut self, shortcut: &Shortcut) -> Option<A> {
+        self.bindings.remove(shortcut)
+    }
+
+    /// Returns the action bound to the shortcut triggered by `key`
+    pub fn lookup(&self, key: &Key) -> Option<&A> {
+        self.bindings.get(&Shortcut::from(key))
+    }
+
+    /// Returns `true` if no shortcut is bound
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(c: char, modifiers: Modifiers) -> Key {
+        Key { key: KeyVal::Character(c), modifiers, code: 0, is_repeat: false }
+    }
+
+    #[test]
+    fn parse_shortcut() {
+        let sc: Shortcut = "Ctrl+Shift+Z".parse().unwrap();
+        assert_eq!(sc.key(), KeyVal::Character('z'));
+        assert_eq!(sc.modifiers(), Modifiers::CTRL | Modifiers::SHIFT);
+
+        let sc: Shortcut = "alt + f4".parse().unwrap();
+        assert_eq!(sc.key(), KeyVal::Special(SpecialKey::F4));
+        assert_eq!(sc.modifiers(), Modifiers::ALT);
+
+        let sc: Shortcut = "Control+Esc".parse().unwrap();
+        assert_eq!(sc, Shortcut::new(KeyVal::Special(SpecialKey::Escape), Modifiers::CTRL));
+
+        assert_eq!("Space".parse::<Shortcut>().unwrap().key(), KeyVal::Character(' '));
+    }
+
+    #[test]
+    fn special_keys_roundtrip() {
+        assert_eq!(SPECIAL_KEY_NAMES.len(), SpecialKey::ALL.len());
+        for &sk in SpecialKey::ALL {
+            for &modifiers in &[Modifiers::NONE, Modifiers::CTRL | Modifiers::SHIFT] {
+                let sc = Shortcut::new(KeyVal::Special(sk), modifiers);
+                assert_eq!(sc.to_string().parse::<Shortcut>(), Ok(sc), "{}", sc);
+            }
+        }
+    }
+
+    #[test]
+    fn character_and_unknown_keys_roundtrip() {
+        let keys = (0..0x3000).filter_map(char::from_u32)
+            .chain(['\u{3000}', '\u{feff}', 'ſ', 'ǅ', 'İ', 'ﬀ', '\u{1f600}'].iter().copied())
+            .map(KeyVal::Character)
+            .chain([0, 0xd800, 0xffff_ffff].iter().map(|&k| KeyVal::Unknown(k)));
+        for key in keys {
+            for &modifiers in &[Modifiers::NONE, Modifiers::CTRL | Modifiers::ALT] {
+                let sc = Shortcut::new(key, modifiers);
+                assert_eq!(sc.to_string().parse::<Shortcut>(), Ok(sc), "{:?}", sc);
+            }
+        }
+    }
+
+    #[test]
+    fn display_keeps_keys_parseable() {
+        let sc = |key| Shortcut::new(key, Modifiers::CTRL).to_string();
+        assert_eq!(sc(KeyVal::Character('ß')), "Ctrl+ß");
+        assert_eq!(sc(KeyVal::Character('\n')), "Ctrl+Char(0xa)");
+        assert_eq!(sc(KeyVal::Character('\u{a0}')), "Ctrl+Char(0xa0)");
+        assert_eq!(sc(KeyVal::Unknown(0xd800)), "Ctrl+Unknown(0xd800)");
+        assert_eq!("ctrl+unknown(0xd800)".parse::<Shortcut>().unwrap().key(), KeyVal::Unknown(0xd800));
+        assert_eq!("Unknown(12)".parse::<Shortcut>(), Err(ShortcutParseError::UnknownKey("Unknown(12)".to_string())));
+        assert_eq!("Char(0xd800)".parse::<Shortcut>(), Err(ShortcutParseError::UnknownKey("Char(0xd800)".to_string())));
+    }
+
+    #[test]
+    fn parse_plus_key() {
+        let sc: Shortcut = "Ctrl++".parse().unwrap();
+        assert_eq!(sc, Shortcut::new(KeyVal::Character('+'), Modifiers::CTRL));
+        assert_eq!("Ctrl+Plus".parse::<Shortcut>().unwrap(), sc);
+        assert_eq!("+".parse::<Shortcut>().unwrap().key(), KeyVal::Character('+'));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Shortcut>(), Err(ShortcutParseError::MissingKey));
+        assert_eq!("Ctrl+".parse::<Shortcut>(), Err(ShortcutParseError::MissingKey));
+        assert_eq!("Hyper+A".parse::<Shortcut>(), Err(ShortcutParseError::UnknownModifier("Hyper".to_string())));
+        assert_eq!("Ctrl+Foo".parse::<Shortcut>(), Err(ShortcutParseError::UnknownKey("Foo".to_string())));
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for s in ["Ctrl+Shift+Z", "Alt+F4", "Ctrl+Plus", "Super+Space", "PageUp", "Ctrl+Alt+Delete", "Ctrl+Keypad5"].iter() {
+            let sc: Shortcut = s.parse().unwrap();
+            assert_eq!(sc.to_string(), *s);
+            assert_eq!(sc.to_string().parse::<Shortcut>().unwrap(), sc);
+        }
+    }
+
+    #[test]
+    fn shift_modified_characters() {
+        let undo: Shortcut = "Ctrl+Z".parse().unwrap();
+        let redo: Shortcut = "Ctrl+Shift+Z".parse().unwrap();
+
+        assert!(undo.matches(&key('z', Modifiers::CTRL)));
+        assert!(!undo.matches(&key('z', Modifiers::CTRL | Modifiers::SHIFT)));
+        assert!(!undo.matches(&key('Z', Modifiers::CTRL)));
+
+        assert!(redo.matches(&key('z', Modifiers::CTRL | Modifiers::SHIFT)));
+        assert!(redo.matches(&key('Z', Modifiers::CTRL | Modifiers::SHIFT)));
+        assert!(redo.matches(&key('Z', Modifiers::CTRL)));
+    }
+
+    #[test]
+    fn shortcut_map() {
+        let mut map = ShortcutMap::new();
+        assert!(map.is_empty());
+        map.insert("Ctrl+Z".parse().unwrap(), 1);
+        map.insert("Ctrl+Shift+Z".parse().unwrap(), 2);
+        assert_eq!(map.insert("Ctrl+z".parse().unwrap(), 3), Some(1));
+
+        assert_eq!(map.lookup(&key('z', Modifiers::CTRL)), Some(&3));
+        assert_eq!(map.lookup(&key('Z', Modifiers::CTRL | Modifiers::SHIFT)), Some(&2));
+        assert_eq!(map.lookup(&key('y', Modifiers::CTRL)), None);
+
+        assert_eq!(map.remove(&"Ctrl+Shift+Z".parse().unwrap()), Some(2));
+        assert_eq!(map.lookup(&key('z', Modifiers::CTRL | Modifiers::SHIFT)), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Comparison of rendered images with snapshots stored as PNG
+//!
+//! Used by [`PuglView::assert_snapshot()`](../view/struct.PuglView.html#method.assert_snapshot)
+//! to catch visual regressions of a UI without a display.
+
+use std::fs;
+use std::path::Path;
+
+use crate::types::surface_pixels;
+
+/// The environment variable that makes snapshot assertions store the
+/// rendered image instead of comparing it
+pub(crate) const BLESS_VAR: &str = "PUGL_BLESS_SNAPSHOTS";
+
+/// Color of the pixels in the diff image that differ too much
+const MISMATCH_COLOR: u32 = 0xffff_0000;
+
+/// Compares `actual` with the snapshot at `path`
+///
+/// If `bless` is set, `actual` is stored as the snapshot. Otherwise
+/// the images must have the same size and no color channel of a
+/// pixel must differ by more than `tolerance`. If they don't, the
+/// rendered image and, if the sizes match, a diff image are saved
+/// next to the snapshot and the error describes the mismatch.
+///
+/// The images of an earlier failing comparison are removed when the
+/// snapshot passes or is blessed.
+pub(crate) fn check(actual: &cairo::ImageSurface, path: &Path, tolerance: u8, bless: bool) -> Result<(), String> {
+    let actual_path = path.with_extension("actual.png");
+    let diff_path = path.with_extension("diff.png");
+
+    if bless {
+        write_png(actual, path)?;
+        return remove_stale(&[&actual_path, &diff_path]);
+    }
+
+    let expected = fs::File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|mut file| cairo::ImageSurface::create_from_png(&mut file).map_err(|e| e.to_string()))
+        .map_err(|e| format!("cannot load snapshot {}: {} (set {} to create it)", path.display(), e, BLESS_VAR))?;
+
+    let width = actual.get_width();
+    let height = actual.get_height();
+
+    if (width, height) != (expected.get_width(), expected.get_height()) {
+        write_png(actual, &actual_path)?;
+        remove_stale(&[&diff_path])?;
+        return Err(format!(
+            "snapshot {} has size {}x{}, rendered image has size {}x{}, saved as {}",
+            path.display(), expected.get_width(), expected.get_height(), width, height, actual_path.display()
+        ));
+    }
+
+    let unsupported = |p: &Path| format!("unsupported image format of {}", p.display());
+    let actual_pixels = surface_pixels(actual).ok_or_else(|| unsupported(&actual_path))?;
+    let expected_pixels = surface_pixels(&expected).ok_or_else(|| unsupported(path))?;
+
+    let mut mismatches = 0;
+    let diff_pixels: Vec<u32> = actual_pixels.iter().zip(expected_pixels.iter())
+        .map(|(&a, &e)| {
+            if max_channel_difference(a, e) > tolerance {
+                mismatches += 1;
+                MISMATCH_COLOR
+            } else {
+                faded(e)
+            }
+        })
+        .collect();
+
+    if mismatches == 0 {
+        return remove_stale(&[&actual_path, &diff_path]);
+    }
+
+    write_png(actual, &actual_path)?;
+    write_png(&surface_from_pixels(&diff_pixels, width, height)?, &diff_path)?;
+    Err(format!(
+        "{} of {} pixels differ from snapshot {} by more than {}, rendered image saved as {}, differences as {}",
+        mismatches, diff_pixels.len(), path.display(), tolerance, actual_path.display(), diff_path.display()
+    ))
+}
+
+/// Returns the largest difference of any channel of two ARGB pixels
+fn max_channel_difference(a: u32, b: u32) -> u8 {
+    (0..4)
+        .map(|i| {
+            let (x, y) = ((a >> (i * 8)) as u8, (b >> (i * 8)) as u8);
+            x.max(y) - x.min(y)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns an opaque light gray of the brightness of `argb`
+fn faded(argb: u32) -> u32 {
+    let alpha = (argb >> 24) & 0xff;
+    let channel = |shift: u32| ((argb >> shift) & 0xff) * alpha / 255;
+    let gray = (channel(16) + channel(8) + channel(0)) / 3;
+    let light = 0xc0 + gray / 4;
+    0xff00_0000 | light << 16 | light << 8 | light
+}
+
+/// Creates an `ARgb32` surface from opaque ARGB pixels
+fn surface_from_pixels(pixels: &[u32], width: i32, height: i32) -> Result<cairo::ImageSurface, String> {
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)
+        .map_err(|e| format!("cannot create diff image: {}", e))?;
+    let stride = surface.get_stride() as usize;
+    {
+        let mut data = surface.get_data().map_err(|e| format!("cannot create diff image: {}", e))?;
+        for (row, line) in data.chunks_mut(stride).zip(pixels.chunks(width as usize)) {
+            for (px, &argb) in row.chunks_mut(4).zip(line.iter()) {
+                px.copy_from_slice(&argb.to_ne_bytes());
+            }
+        }
+    }
+    Ok(surface)
+}
+
+/// Removes the files at `paths` that are left from an earlier comparison
+fn remove_stale(paths: &[&Path]) -> Result<(), String> {
+    for path in paths {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("cannot remove {}: {}", path.display(), e));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Writes `surface` as PNG to `path`, creating the directory if necessary
+fn write_png(surface: &cairo::ImageSurface, path: &Path) -> Result<(), String> {
+    let error = |e: &dyn std::fmt::Display| format!("cannot write {}: {}", path.display(), e);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| error(&e))?;
+    }
+    let mut file = fs::File::create(path).map_err(|e| error(&e))?;
+    surface.write_to_png(&mut file).map_err(|e| error(&e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image(pixels: &[u32], width: i32) -> cairo::ImageSurface {
+        surface_from_pixels(pixels, width, pixels.len() as i32 / width).unwrap()
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join("pugl-sys-snapshot-test").join(name)
+    }
+
+    #[test]
+    fn channel_difference() {
+        assert_eq!(max_channel_difference(0xff10_2030, 0xff10_2030), 0);
+        assert_eq!(max_channel_difference(0xff10_2030, 0xff12_1f30), 2);
+        assert_eq!(max_channel_difference(0x0010_2030, 0xff10_2030), 255);
+    }
+
+    #[test]
+    fn bless_and_compare() {
+        let path = temp_path("bless.png");
+        let pixels = [0xff00_00ff, 0xffff_0000, 0xff00_ff00, 0xff80_8080];
+
+        check(&image(&pixels, 2), &path, 0, true).unwrap();
+        check(&image(&pixels, 2), &path, 0, false).unwrap();
+
+        let close = [0xff00_00fd, 0xffff_0000, 0xff00_ff00, 0xff80_8082];
+        check(&image(&close, 2), &path, 2, false).unwrap();
+        assert!(!path.with_extension("diff.png").exists());
+
+        let msg = check(&image(&close, 2), &path, 1, false).unwrap_err();
+        assert!(msg.starts_with("2 of 4 pixels"), "{}", msg);
+        let mut diff_file = fs::File::open(path.with_extension("diff.png")).unwrap();
+        let diff = cairo::ImageSurface::create_from_png(&mut diff_file).unwrap();
+        let diff_pixels = surface_pixels(&diff).unwrap();
+        assert_eq!(diff_pixels[0], MISMATCH_COLOR);
+        assert_ne!(diff_pixels[1], MISMATCH_COLOR);
+        assert_eq!(diff_pixels[3], MISMATCH_COLOR);
+
+        let mut actual_file = fs::File::open(path.with_extension("actual.png")).unwrap();
+        let actual = cairo::ImageSurface::create_from_png(&mut actual_file).unwrap();
+        assert_eq!(surface_pixels(&actual).unwrap(), close.to_vec());
+
+        check(&image(&close, 2), &path, 2, false).unwrap();
+        assert!(!path.with_extension("actual.png").exists());
+        assert!(!path.with_extension("diff.png").exists());
+
+        check(&image(&close, 2), &path, 1, false).unwrap_err();
+        check(&image(&close, 2), &path, 0, true).unwrap();
+        assert!(!path.with_extension("actual.png").exists());
+        assert!(!path.with_extension("diff.png").exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let path = temp_path("size.png");
+        check(&image(&[0xff00_0000; 4], 2), &path, 0, true).unwrap();
+        let msg = check(&image(&[0xff00_0000; 4], 4), &path, 255, false).unwrap_err();
+        assert!(msg.contains("has size 2x2, rendered image has size 4x1"), "{}", msg);
+        assert!(path.with_extension("actual.png").exists());
+        assert!(!path.with_extension("diff.png").exists());
+        fs::remove_file(path.with_extension("actual.png")).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_snapshot() {
+        let msg = check(&image(&[0xff00_0000], 1), &temp_path("missing.png"), 0, false).unwrap_err();
+        assert!(msg.contains(BLESS_VAR), "{}", msg);
+    }
+}
@@ -1,5 +1,5 @@
 
-use std::ops::{Add, AddAssign};
+use std::ops::{Add, AddAssign, Sub};
 use std::char;
 
 use crate::pugl as p;
@@ -7,6 +7,7 @@ use crate::pugl as p;
 /// Representing coordinates on a widget
 ///
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coord {
     /// x coordinate
     pub x: f64,
@@ -51,8 +52,20 @@ impl AddAssign for Coord {
     }
 }
 
+impl Sub for Coord {
+    type Output = Coord;
+
+    fn sub (self, other: Coord) -> Coord {
+        Coord {
+            x: self.x - other.x,
+            y: self.y - other.y
+        }
+    }
+}
+
 /// Representing a size of a rectangle
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size {
     /// width
     pub w: f64,
@@ -93,7 +106,8 @@ impl Add for Size {
 ///
 /// This This is used to describe things like view position and size.  Pugl generally
 /// uses coordinates where the top left corner is 0,0.
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     /// The position of the upper left corner of the `Rect`
     pub pos: Coord,
@@ -101,6 +115,78 @@ pub struct Rect {
     pub size: Size
 }
 
+impl Rect {
+    /// Returns the center of the `Rect`
+    ///
+    /// ```
+    /// let r = pugl_sys::Rect {
+    ///     pos: pugl_sys::Coord { x: 10., y: 20. },
+    ///     size: pugl_sys::Size { w: 100., h: 50. }
+    /// };
+    /// assert_eq!(r.center(), pugl_sys::Coord { x: 60., y: 45. });
+    /// ```
+    pub fn center(&self) -> Coord {
+        Coord {
+            x: self.pos.x + self.size.w / 2.,
+            y: self.pos.y + self.size.h / 2.
+        }
+    }
+
+    /// Returns the area where `self` and `other` overlap or `None` if they don't
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.pos.x.max(other.pos.x);
+        let top = self.pos.y.max(other.pos.y);
+        let right = (self.pos.x + self.size.w).min(other.pos.x + other.size.w);
+        let bottom = (self.pos.y + self.size.h).min(other.pos.y + other.size.h);
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+        Some(Rect {
+            pos: Coord { x: left, y: top },
+            size: Size { w: right - left, h: bottom - top }
+        })
+    }
+
+    /// Returns the smallest `Rect` containing both `self` and `other`
+    pub fn union(&self, other: &Rect) -> Rect {
+        let left = self.pos.x.min(other.pos.x);
+        let top = self.pos.y.min(other.pos.y);
+        let right = (self.pos.x + self.size.w).max(other.pos.x + other.size.w);
+        let bottom = (self.pos.y + self.size.h).max(other.pos.y + other.size.h);
+        Rect {
+            pos: Coord { x: left, y: top },
+            size: Size { w: right - left, h: bottom - top }
+        }
+    }
+
+    /// Returns a `Rect` of the same size centered in `area`
+    pub fn centered_in(&self, area: &Rect) -> Rect {
+        Rect {
+            pos: Coord {
+                x: area.pos.x + (area.size.w - self.size.w) / 2.,
+                y: area.pos.y + (area.size.h - self.size.h) / 2.
+            },
+            size: self.size
+        }
+    }
+
+    /// Returns a `Rect` of the same size moved as little as possible to lie within `area`
+    ///
+    /// If the `Rect` is larger than `area`, its upper left corner is
+    /// placed on the one of `area`.
+    pub fn clamped_to(&self, area: &Rect) -> Rect {
+        let clamp = |pos: f64, size: f64, min: f64, extent: f64| pos.min(min + extent - size).max(min);
+        Rect {
+            pos: Coord {
+                x: clamp(self.pos.x, self.size.w, area.pos.x, area.size.w),
+                y: clamp(self.pos.y, self.size.h, area.pos.y, area.size.h)
+            },
+            size: self.size
+        }
+    }
+}
+
 impl From<p::PuglRect> for Rect {
     fn from(pr: p::PuglRect) -> Rect {
         Rect {
@@ -121,20 +207,87 @@ impl From <Rect> for p::PuglRect {
     }
 }
 
+/// A monitor connected to the screen
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Monitor {
+    /// The name of the monitor as reported by the window system
+    pub name: String,
+    /// The position and size of the monitor in screen coordinates
+    pub geometry: Rect,
+    /// Whether the monitor is the primary one
+    pub primary: bool
+}
+
+impl Monitor {
+    /// Returns the monitor that `frame` is on
+    ///
+    /// That is the monitor that `frame` overlaps the most. If `frame`
+    /// is on none of the `monitors`, the primary monitor is returned,
+    /// or the first one if none is primary.
+    pub fn find_for<'a>(monitors: &'a [Monitor], frame: &Rect) -> Option<&'a Monitor> {
+        Self::overlapped_most(monitors, frame)
+            .or_else(|| monitors.iter().find(|m| m.primary))
+            .or_else(|| monitors.first())
+    }
+
+    /// Returns where to place `frame` to make it visible on one of the `monitors`
+    ///
+    /// If `frame` is partly on one of the monitors, it is moved to be
+    /// completely on it. Otherwise it is centered on the primary
+    /// monitor. This is useful to restore a saved window position
+    /// after the monitor setup has changed.
+    ///
+    /// ```
+    /// use pugl_sys::*;
+    /// let monitors = [
+    ///     Monitor {
+    ///         name: "left".to_string(),
+    ///         geometry: Rect { pos: Coord { x: 0., y: 0. }, size: Size { w: 800., h: 600. } },
+    ///         primary: true
+    ///     }
+    /// ];
+    /// let saved = Rect { pos: Coord { x: 900., y: 100. }, size: Size { w: 200., h: 100. } };
+    /// assert_eq!(Monitor::place_frame(&monitors, saved).pos, Coord { x: 300., y: 250. });
+    /// ```
+    pub fn place_frame(monitors: &[Monitor], frame: Rect) -> Rect {
+        if let Some(monitor) = Self::overlapped_most(monitors, &frame) {
+            return frame.clamped_to(&monitor.geometry);
+        }
+        match Self::find_for(monitors, &frame) {
+            Some(monitor) => frame.centered_in(&monitor.geometry),
+            None => frame
+        }
+    }
+
+    fn overlapped_most<'a>(monitors: &'a [Monitor], frame: &Rect) -> Option<&'a Monitor> {
+        monitors.iter()
+            .filter_map(|m| m.geometry.intersection(frame).map(|i| (m, i.size.w * i.size.h)))
+            .fold(None, |best: Option<(&Monitor, f64)>, (m, area)| match best {
+                Some((_, best_area)) if best_area >= area => best,
+                _ => Some((m, area))
+            })
+            .map(|(m, _)| m)
+    }
+}
 
 /// The context of a GUI event
 #[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventContext {
     /// View relative position of the event
     pub pos: Coord,
     /// Root relative position of the event
     pub pos_root: Coord,
     /// The time of the event in seconds
-    pub time: f64
+    pub time: f64,
+    /// The event flags
+    pub flags: EventFlags
 }
 
 /// Keys not representing a character
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpecialKey {
     Backspace,
     Escape,
@@ -174,12 +327,144 @@ pub enum SpecialKey {
     KeyNumLock,
     KeyPrintScreen,
     KeyPause,
-    None
+    None,
+    Keypad0,
+    Keypad1,
+    Keypad2,
+    Keypad3,
+    Keypad4,
+    Keypad5,
+    Keypad6,
+    Keypad7,
+    Keypad8,
+    Keypad9,
+    KeypadDecimal,
+    KeypadEnter,
+    KeypadAdd,
+    KeypadSubtract,
+    KeypadMultiply,
+    KeypadDivide,
+    VolumeMute,
+    VolumeDown,
+    VolumeUp,
+    MediaPlayPause,
+    MediaStop,
+    MediaPrevious,
+    MediaNext
 }
 
-impl From<p::PuglKey> for SpecialKey {
-    fn from (k: p::PuglKey) -> SpecialKey {
-        match k {
+impl SpecialKey {
+    /// Returns the keypad or media key at the position of `physical_key`
+    ///
+    /// These keys are not known to pugl, so they are identified by
+    /// their position on the keyboard.
+    fn from_physical_key(physical_key: PhysicalKey) -> Option<SpecialKey> {
+        match physical_key {
+            PhysicalKey::Numpad0 => Some(SpecialKey::Keypad0),
+            PhysicalKey::Numpad1 => Some(SpecialKey::Keypad1),
+            PhysicalKey::Numpad2 => Some(SpecialKey::Keypad2),
+            PhysicalKey::Numpad3 => Some(SpecialKey::Keypad3),
+            PhysicalKey::Numpad4 => Some(SpecialKey::Keypad4),
+            PhysicalKey::Numpad5 => Some(SpecialKey::Keypad5),
+            PhysicalKey::Numpad6 => Some(SpecialKey::Keypad6),
+            PhysicalKey::Numpad7 => Some(SpecialKey::Keypad7),
+            PhysicalKey::Numpad8 => Some(SpecialKey::Keypad8),
+            PhysicalKey::Numpad9 => Some(SpecialKey::Keypad9),
+            PhysicalKey::NumpadDecimal => Some(SpecialKey::KeypadDecimal),
+            PhysicalKey::NumpadEnter => Some(SpecialKey::KeypadEnter),
+            PhysicalKey::NumpadAdd => Some(SpecialKey::KeypadAdd),
+            PhysicalKey::NumpadSubtract => Some(SpecialKey::KeypadSubtract),
+            PhysicalKey::NumpadMultiply => Some(SpecialKey::KeypadMultiply),
+            PhysicalKey::NumpadDivide => Some(SpecialKey::KeypadDivide),
+            PhysicalKey::AudioVolumeMute => Some(SpecialKey::VolumeMute),
+            PhysicalKey::AudioVolumeDown => Some(SpecialKey::VolumeDown),
+            PhysicalKey::AudioVolumeUp => Some(SpecialKey::VolumeUp),
+            PhysicalKey::MediaPlayPause => Some(SpecialKey::MediaPlayPause),
+            PhysicalKey::MediaStop => Some(SpecialKey::MediaStop),
+            PhysicalKey::MediaTrackPrevious => Some(SpecialKey::MediaPrevious),
+            PhysicalKey::MediaTrackNext => Some(SpecialKey::MediaNext),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+impl SpecialKey {
+    /// All special keys, for tests that need to cover every one
+    pub(crate) const ALL: &'static [SpecialKey] = &[
+        SpecialKey::Backspace,
+        SpecialKey::Escape,
+        SpecialKey::Delete,
+        SpecialKey::F1,
+        SpecialKey::F2,
+        SpecialKey::F3,
+        SpecialKey::F4,
+        SpecialKey::F5,
+        SpecialKey::F6,
+        SpecialKey::F7,
+        SpecialKey::F8,
+        SpecialKey::F9,
+        SpecialKey::F10,
+        SpecialKey::F11,
+        SpecialKey::F12,
+        SpecialKey::Left,
+        SpecialKey::Up,
+        SpecialKey::Right,
+        SpecialKey::Down,
+        SpecialKey::PageUp,
+        SpecialKey::PageDown,
+        SpecialKey::Home,
+        SpecialKey::End,
+        SpecialKey::Insert,
+        SpecialKey::ShiftL,
+        SpecialKey::ShiftR,
+        SpecialKey::CtrlL,
+        SpecialKey::CtrlR,
+        SpecialKey::AltL,
+        SpecialKey::AltR,
+        SpecialKey::SuperL,
+        SpecialKey::SuperR,
+        SpecialKey::KeyMenu,
+        SpecialKey::KeyCapsLock,
+        SpecialKey::KeyScrollLock,
+        SpecialKey::KeyNumLock,
+        SpecialKey::KeyPrintScreen,
+        SpecialKey::KeyPause,
+        SpecialKey::None,
+        SpecialKey::Keypad0,
+        SpecialKey::Keypad1,
+        SpecialKey::Keypad2,
+        SpecialKey::Keypad3,
+        SpecialKey::Keypad4,
+        SpecialKey::Keypad5,
+        SpecialKey::Keypad6,
+        SpecialKey::Keypad7,
+        SpecialKey::Keypad8,
+        SpecialKey::Keypad9,
+        SpecialKey::KeypadDecimal,
+        SpecialKey::KeypadEnter,
+        SpecialKey::KeypadAdd,
+        SpecialKey::KeypadSubtract,
+        SpecialKey::KeypadMultiply,
+        SpecialKey::KeypadDivide,
+        SpecialKey::VolumeMute,
+        SpecialKey::VolumeDown,
+        SpecialKey::VolumeUp,
+        SpecialKey::MediaPlayPause,
+        SpecialKey::MediaStop,
+        SpecialKey::MediaPrevious,
+        SpecialKey::MediaNext
+    ];
+}
+
+impl SpecialKey {
+    /// Returns the `SpecialKey` of a pugl key, `None` if it is not a special key
+    ///
+    /// This is the fallible counterpart of the `From<PuglKey>`
+    /// conversion, which maps keys that are not special to
+    /// `SpecialKey::None`.
+    pub fn from_pugl_key(k: p::PuglKey) -> Option<SpecialKey> {
+        Some(match k {
             p::PuglKey_PUGL_KEY_BACKSPACE => SpecialKey::Backspace,
             p::PuglKey_PUGL_KEY_ESCAPE => SpecialKey::Escape,
             p::PuglKey_PUGL_KEY_DELETE => SpecialKey::Delete,
@@ -218,8 +503,15 @@ impl From<p::PuglKey> for SpecialKey {
             p::PuglKey_PUGL_KEY_NUM_LOCK => SpecialKey::KeyNumLock,
             p::PuglKey_PUGL_KEY_PRINT_SCREEN => SpecialKey::KeyPrintScreen,
             p::PuglKey_PUGL_KEY_PAUSE => SpecialKey::KeyPause,
-            _ => SpecialKey::None
-        }
+            _ => return None
+        })
+    }
+}
+
+impl From<p::PuglKey> for SpecialKey {
+    /// Converts a pugl key into a `SpecialKey`, `SpecialKey::None` if it is not a special key
+    fn from (k: p::PuglKey) -> SpecialKey {
+        SpecialKey::from_pugl_key(k).unwrap_or(SpecialKey::None)
     }
 }
 
@@ -238,24 +530,224 @@ bitflags! {
 /// System's key code
 type KeyCode = u32;
 
+/// A key by its position on the keyboard, independent of the keyboard layout
+///
+/// The keys are named after the key they represent on a US QWERTY
+/// keyboard, following the USB HID usage names as used by the `code`
+/// of W3C keyboard events. For example `KeyZ` is the key left of `KeyX`
+/// in the lower letter row, even on a German keyboard where it is
+/// labeled Y.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhysicalKey {
+    Escape,
+    Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, Digit0,
+    Minus,
+    Equal,
+    Backspace,
+    Tab,
+    KeyQ, KeyW, KeyE, KeyR, KeyT, KeyY, KeyU, KeyI, KeyO, KeyP,
+    BracketLeft,
+    BracketRight,
+    Enter,
+    ControlLeft,
+    KeyA, KeyS, KeyD, KeyF, KeyG, KeyH, KeyJ, KeyK, KeyL,
+    Semicolon,
+    Quote,
+    Backquote,
+    ShiftLeft,
+    Backslash,
+    KeyZ, KeyX, KeyC, KeyV, KeyB, KeyN, KeyM,
+    Comma,
+    Period,
+    Slash,
+    ShiftRight,
+    AltLeft,
+    Space,
+    CapsLock,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    NumLock,
+    ScrollLock,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    NumpadDecimal,
+    NumpadEnter,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    IntlBackslash,
+    ControlRight,
+    AltRight,
+    PrintScreen,
+    Pause,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    MetaLeft,
+    MetaRight,
+    ContextMenu,
+    AudioVolumeMute,
+    AudioVolumeDown,
+    AudioVolumeUp,
+    MediaPlayPause,
+    MediaStop,
+    MediaTrackPrevious,
+    MediaTrackNext,
+    /// A key not covered by the other variants by its system's key code
+    Unknown(u32)
+}
+
+/// The physical keys by their Linux evdev code, X11 key codes are offset by 8
+const PHYSICAL_KEYS: &[(u32, PhysicalKey)] = &[
+    (1, PhysicalKey::Escape),
+    (2, PhysicalKey::Digit1), (3, PhysicalKey::Digit2), (4, PhysicalKey::Digit3),
+    (5, PhysicalKey::Digit4), (6, PhysicalKey::Digit5), (7, PhysicalKey::Digit6),
+    (8, PhysicalKey::Digit7), (9, PhysicalKey::Digit8), (10, PhysicalKey::Digit9),
+    (11, PhysicalKey::Digit0),
+    (12, PhysicalKey::Minus),
+    (13, PhysicalKey::Equal),
+    (14, PhysicalKey::Backspace),
+    (15, PhysicalKey::Tab),
+    (16, PhysicalKey::KeyQ), (17, PhysicalKey::KeyW), (18, PhysicalKey::KeyE),
+    (19, PhysicalKey::KeyR), (20, PhysicalKey::KeyT), (21, PhysicalKey::KeyY),
+    (22, PhysicalKey::KeyU), (23, PhysicalKey::KeyI), (24, PhysicalKey::KeyO),
+    (25, PhysicalKey::KeyP),
+    (26, PhysicalKey::BracketLeft),
+    (27, PhysicalKey::BracketRight),
+    (28, PhysicalKey::Enter),
+    (29, PhysicalKey::ControlLeft),
+    (30, PhysicalKey::KeyA), (31, PhysicalKey::KeyS), (32, PhysicalKey::KeyD),
+    (33, PhysicalKey::KeyF), (34, PhysicalKey::KeyG), (35, PhysicalKey::KeyH),
+    (36, PhysicalKey::KeyJ), (37, PhysicalKey::KeyK), (38, PhysicalKey::KeyL),
+    (39, PhysicalKey::Semicolon),
+    (40, PhysicalKey::Quote),
+    (41, PhysicalKey::Backquote),
+    (42, PhysicalKey::ShiftLeft),
+    (43, PhysicalKey::Backslash),
+    (44, PhysicalKey::KeyZ), (45, PhysicalKey::KeyX), (46, PhysicalKey::KeyC),
+    (47, PhysicalKey::KeyV), (48, PhysicalKey::KeyB), (49, PhysicalKey::KeyN),
+    (50, PhysicalKey::KeyM),
+    (51, PhysicalKey::Comma),
+    (52, PhysicalKey::Period),
+    (53, PhysicalKey::Slash),
+    (54, PhysicalKey::ShiftRight),
+    (55, PhysicalKey::NumpadMultiply),
+    (56, PhysicalKey::AltLeft),
+    (57, PhysicalKey::Space),
+    (58, PhysicalKey::CapsLock),
+    (59, PhysicalKey::F1), (60, PhysicalKey::F2), (61, PhysicalKey::F3),
+    (62, PhysicalKey::F4), (63, PhysicalKey::F5), (64, PhysicalKey::F6),
+    (65, PhysicalKey::F7), (66, PhysicalKey::F8), (67, PhysicalKey::F9),
+    (68, PhysicalKey::F10),
+    (69, PhysicalKey::NumLock),
+    (70, PhysicalKey::ScrollLock),
+    (71, PhysicalKey::Numpad7), (72, PhysicalKey::Numpad8), (73, PhysicalKey::Numpad9),
+    (74, PhysicalKey::NumpadSubtract),
+    (75, PhysicalKey::Numpad4), (76, PhysicalKey::Numpad5), (77, PhysicalKey::Numpad6),
+    (78, PhysicalKey::NumpadAdd),
+    (79, PhysicalKey::Numpad1), (80, PhysicalKey::Numpad2), (81, PhysicalKey::Numpad3),
+    (82, PhysicalKey::Numpad0),
+    (83, PhysicalKey::NumpadDecimal),
+    (86, PhysicalKey::IntlBackslash),
+    (87, PhysicalKey::F11),
+    (88, PhysicalKey::F12),
+    (96, PhysicalKey::NumpadEnter),
+    (97, PhysicalKey::ControlRight),
+    (98, PhysicalKey::NumpadDivide),
+    (99, PhysicalKey::PrintScreen),
+    (100, PhysicalKey::AltRight),
+    (102, PhysicalKey::Home),
+    (103, PhysicalKey::ArrowUp),
+    (104, PhysicalKey::PageUp),
+    (105, PhysicalKey::ArrowLeft),
+    (106, PhysicalKey::ArrowRight),
+    (107, PhysicalKey::End),
+    (108, PhysicalKey::ArrowDown),
+    (109, PhysicalKey::PageDown),
+    (110, PhysicalKey::Insert),
+    (111, PhysicalKey::Delete),
+    (113, PhysicalKey::AudioVolumeMute),
+    (114, PhysicalKey::AudioVolumeDown),
+    (115, PhysicalKey::AudioVolumeUp),
+    (119, PhysicalKey::Pause),
+    (125, PhysicalKey::MetaLeft),
+    (126, PhysicalKey::MetaRight),
+    (127, PhysicalKey::ContextMenu),
+    (163, PhysicalKey::MediaTrackNext),
+    (164, PhysicalKey::MediaPlayPause),
+    (165, PhysicalKey::MediaTrackPrevious),
+    (166, PhysicalKey::MediaStop),
+];
+
+/// Offset of X11 key codes to Linux evdev codes
+const X11_KEYCODE_OFFSET: u32 = 8;
+
+impl PhysicalKey {
+    /// Returns the physical key by the system's key code
+    ///
+    /// ```
+    /// use pugl_sys::PhysicalKey;
+    ///
+    /// assert_eq!(PhysicalKey::from_keycode(52), PhysicalKey::KeyZ);
+    /// assert_eq!(PhysicalKey::from_keycode(0), PhysicalKey::Unknown(0));
+    /// ```
+    pub fn from_keycode(code: KeyCode) -> PhysicalKey {
+        code.checked_sub(X11_KEYCODE_OFFSET)
+            .and_then(|evdev| PHYSICAL_KEYS.iter().find(|&&(c, _)| c == evdev))
+            .map_or(PhysicalKey::Unknown(code), |&(_, key)| key)
+    }
+
+    /// Returns the system's key code of the physical key
+    pub fn keycode(&self) -> KeyCode {
+        match *self {
+            PhysicalKey::Unknown(code) => code,
+            key => PHYSICAL_KEYS.iter()
+                .find(|&&(_, k)| k == key)
+                .map_or(0, |&(evdev, _)| evdev + X11_KEYCODE_OFFSET)
+        }
+    }
+}
+
 /// Representing a key from the keyboard
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeyVal {
     /// A Character key
     Character(char),
     /// A special key (non-character)
-    Special(SpecialKey)
+    Special(SpecialKey),
+    /// A key unknown to pugl-sys by the key value of pugl
+    ///
+    /// The value is not a valid `char`, or 0 if pugl could not
+    /// translate the key at all. The key can still be identified by
+    /// the `code` of the [`Key`](struct.Key.html).
+    Unknown(u32)
 }
 
 /// Key with keyboard modifiers
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Key {
     /// The actual key
     pub key: KeyVal,
     /// The modifiers to be used with the [`Modifiers`](struct.Modifiers.html) struct
     pub modifiers: Modifiers,
     /// System's code for the key
-    pub code: KeyCode
+    ///
+    /// This is the raw platform key code, e.g. the X11 keycode, see
+    /// [`physical_key()`](#method.physical_key) for the key it denotes.
+    /// With the `serde` feature it is serialized as is, so serialized
+    /// keys only identify the physical key on the same platform.
+    pub code: KeyCode,
+    /// `true` if the key press is a repeat of a key being held down
+    pub is_repeat: bool
 }
 
 impl Key {
@@ -265,12 +757,14 @@ impl Key {
     /// let char_key = pugl_sys::Key {
     ///     key: pugl_sys::KeyVal::Character('A'),
     ///     modifiers: pugl_sys::Modifiers::SHIFT,
-    ///     code: 38
+    ///     code: 38,
+    ///     is_repeat: false
     /// };
     /// let special_key = pugl_sys::Key {
     ///     key: pugl_sys::KeyVal::Special(pugl_sys::SpecialKey::F1),
     ///     modifiers: pugl_sys::Modifiers::NONE,
-    ///     code: 67
+    ///     code: 67,
+    ///     is_repeat: false
     /// };
     ///
     /// assert_eq!(char_key.try_char(), Some('A'));
@@ -282,15 +776,39 @@ impl Key {
             _ => None
         }
     }
+
+    /// Returns the key by its position on the keyboard
+    ///
+    /// Unlike [`key`](#structfield.key) this does not depend on the
+    /// keyboard layout, so it is suitable for positional key handling
+    /// like mapping keyboard rows to the keys of a piano.
+    ///
+    /// ```
+    /// use pugl_sys::*;
+    ///
+    /// // 'y' on a German keyboard
+    /// let key = Key { key: KeyVal::Character('y'), modifiers: Modifiers::NONE, code: 52, is_repeat: false };
+    /// assert_eq!(key.physical_key(), PhysicalKey::KeyZ);
+    /// ```
+    pub fn physical_key(&self) -> PhysicalKey {
+        PhysicalKey::from_keycode(self.code)
+    }
 }
 
 impl From<p::PuglEventKey> for KeyVal {
+    /// Keypad and media keys are identified by the key code, as the
+    /// key value of the keypad depends on the state of NumLock.
     fn from(ke: p::PuglEventKey) -> KeyVal {
-        let special_key = SpecialKey::from(ke.key);
-        match special_key {
-            SpecialKey::None => KeyVal::Character (char::from_u32(ke.key).unwrap()),
-            _ => KeyVal::Special (special_key),
+        if let Some(special_key) = SpecialKey::from_physical_key(PhysicalKey::from_keycode(ke.keycode)) {
+            return KeyVal::Special (special_key);
+        }
+        if let Some(special_key) = SpecialKey::from_pugl_key(ke.key) {
+            return KeyVal::Special (special_key);
+        }
+        if ke.key == 0 {
+            return KeyVal::Unknown(0);
         }
+        char::from_u32(ke.key).map_or(KeyVal::Unknown(ke.key), KeyVal::Character)
     }
 }
 
@@ -299,7 +817,8 @@ impl From<p::PuglEventKey> for Key {
         Key {
             key: KeyVal::from (ke),
             code: ke.keycode,
-            modifiers: Modifiers::from_bits_truncate(ke.state)
+            modifiers: Modifiers::from_bits_truncate(ke.state),
+            is_repeat: false
         }
     }
 }
@@ -309,26 +828,113 @@ impl From<p::PuglEventKey> for EventContext {
         EventContext {
             pos: Coord { x: ke.x, y: ke.y },
             pos_root: Coord { x: ke.xRoot, y: ke.yRoot },
-            time: ke.time
+            time: ke.time,
+            flags: EventFlags::from_bits_truncate(ke.flags)
         }
     }
 }
 
-/// Representing a mouse button
+/// A mouse button
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Button {
+    /// The primary button, usually the left one
+    Left,
+    /// The middle button, often the scroll wheel
+    Middle,
+    /// The secondary button, usually the right one
+    Right,
+    /// The side button to navigate back
+    Back,
+    /// The side button to navigate forward
+    Forward,
+    /// Any other button by the system's button number
+    Other(u32)
+}
+
+impl Button {
+    /// Returns the system's number of the button
+    ///
+    /// ```
+    /// assert_eq!(pugl_sys::Button::Right.number(), 3);
+    /// assert_eq!(pugl_sys::Button::from(3), pugl_sys::Button::Right);
+    /// ```
+    pub fn number(&self) -> u32 {
+        match *self {
+            Button::Left => 1,
+            Button::Middle => 2,
+            Button::Right => 3,
+            Button::Back => 8,
+            Button::Forward => 9,
+            Button::Other(num) => num
+        }
+    }
+}
+
+impl From<u32> for Button {
+    fn from(num: u32) -> Button {
+        match num {
+            1 => Button::Left,
+            2 => Button::Middle,
+            3 => Button::Right,
+            8 => Button::Back,
+            9 => Button::Forward,
+            _ => Button::Other(num)
+        }
+    }
+}
+
+bitflags! {
+    /// The set of pressed mouse buttons
+    ///
+    /// Buttons other than the named ones are not tracked.
+    #[derive(Default)]
+    pub struct MouseButtons: u32 {
+        const NONE    = 0;
+        const LEFT    = 1;
+        const MIDDLE  = 2;
+        const RIGHT   = 4;
+        const BACK    = 8;
+        const FORWARD = 16;
+    }
+}
+
+impl From<Button> for MouseButtons {
+    fn from(button: Button) -> MouseButtons {
+        match button {
+            Button::Left => MouseButtons::LEFT,
+            Button::Middle => MouseButtons::MIDDLE,
+            Button::Right => MouseButtons::RIGHT,
+            Button::Back => MouseButtons::BACK,
+            Button::Forward => MouseButtons::FORWARD,
+            Button::Other(_) => MouseButtons::NONE
+        }
+    }
+}
+
+/// Representing a mouse button event
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MouseButton {
-    /// The number of the mouse button
-    pub num: u32,
+    /// The mouse button
+    pub button: Button,
     /// Keyboard modifiers, when the mouse button event happened
     /// to be used with the [`Modifiers`](struct.Modifiers.html) struct.
-    pub modifiers: Modifiers
+    pub modifiers: Modifiers,
+    /// The number of clicks in a row, e.g. 2 for a double click
+    ///
+    /// Clicks are counted if the same button is pressed again within
+    /// the system's double click time and distance. A button release
+    /// carries the count of the press it belongs to.
+    pub click_count: u32
 }
 
 impl From<p::PuglEventButton> for MouseButton {
     fn from(be: p::PuglEventButton) -> MouseButton {
         MouseButton {
-            num: be.button,
-            modifiers: Modifiers::from_bits_truncate(be.state)
+            button: Button::from(be.button),
+            modifiers: Modifiers::from_bits_truncate(be.state),
+            click_count: 1
         }
     }
 }
@@ -338,12 +944,18 @@ impl From<p::PuglEventButton> for EventContext {
         EventContext {
             pos: Coord { x: be.x, y: be.y },
             pos_root: Coord { x: be.xRoot, y: be.yRoot },
-            time: be.time
+            time: be.time,
+            flags: EventFlags::from_bits_truncate(be.flags)
         }
     }
 }
 
 bitflags! {
+    /// Flags of an event
+    ///
+    /// `IS_SEND_EVENT` marks events that were sent by some application
+    /// rather than by the windowing system. `IS_HINT` marks motion
+    /// events that are only a hint that the pointer has moved.
     #[derive(Default)]
     pub struct EventFlags: u32 {
         const NONE = 0;
@@ -354,9 +966,12 @@ bitflags! {
 
 /// Context of a pointer event
 #[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MotionContext {
     /// Keyboard modifiers to be used with the [`Modifiers`](struct.Modifiers.html) struct.
     pub modifiers: Modifiers,
+    /// The mouse buttons being pressed during the motion
+    pub buttons: MouseButtons,
     /// The event flags
     pub flags: EventFlags
 }
@@ -365,6 +980,7 @@ impl From<p::PuglEventMotion> for MotionContext {
     fn from (me: p::PuglEventMotion) -> MotionContext {
         MotionContext {
             modifiers: Modifiers::from_bits_truncate(me.state),
+            buttons: MouseButtons::NONE,
             flags: EventFlags::from_bits_truncate(me.flags)
         }
     }
@@ -375,11 +991,78 @@ impl From<p::PuglEventMotion> for EventContext {
         EventContext {
             pos: Coord { x: me.x, y: me.y },
             pos_root: Coord { x: me.xRoot, y: me.yRoot },
-            time: me.time
+            time: me.time,
+            flags: EventFlags::from_bits_truncate(me.flags)
         }
     }
 }
 
+/// A pointer motion in relative pointer mode
+///
+/// See [`set_relative_pointer_mode()`](trait.PuglViewTrait.html#method.set_relative_pointer_mode).
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelativeMotion {
+    /// The distance the pointer has moved
+    pub delta: Coord,
+    /// Keyboard modifiers to be used with the [`Modifiers`](struct.Modifiers.html) struct.
+    pub modifiers: Modifiers,
+    /// The mouse buttons being pressed during the motion
+    pub buttons: MouseButtons
+}
+
+/// The direction or kind of a scroll event
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollDirection {
+    /// A discrete scroll up, e.g. by a mouse wheel tick
+    Up,
+    /// A discrete scroll down
+    Down,
+    /// A discrete scroll left
+    Left,
+    /// A discrete scroll right
+    Right,
+    /// A smooth scroll in any direction, e.g. from a touchpad
+    Smooth
+}
+
+impl From<p::PuglScrollDirection> for ScrollDirection {
+    fn from(sd: p::PuglScrollDirection) -> ScrollDirection {
+        match sd {
+            p::PuglScrollDirection_PUGL_SCROLL_UP => ScrollDirection::Up,
+            p::PuglScrollDirection_PUGL_SCROLL_DOWN => ScrollDirection::Down,
+            p::PuglScrollDirection_PUGL_SCROLL_LEFT => ScrollDirection::Left,
+            p::PuglScrollDirection_PUGL_SCROLL_RIGHT => ScrollDirection::Right,
+            _ => ScrollDirection::Smooth
+        }
+    }
+}
+
+/// The phase of a scroll gesture
+///
+/// The windowing system does not report gestures, so the phase is
+/// derived from the timing of smooth scroll events: a gesture ends
+/// when no smooth scroll follows within 0.15 seconds. So all phases
+/// are heuristic. There is no information about momentum scrolling.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollPhase {
+    /// The scroll is not part of a gesture, like discrete scrolls
+    None,
+    /// The first smooth scroll of a gesture
+    Begin,
+    /// A subsequent smooth scroll of a gesture
+    Update,
+    /// The gesture is over, the scroll distance is zero
+    ///
+    /// Sent before the next event that is not a smooth scroll, like
+    /// a pointer motion, or if no smooth scroll has followed in time.
+    /// As the end cannot wake up the event loop, the latter happens
+    /// when [`update()`](trait.PuglViewTrait.html#method.update) returns.
+    End
+}
+
 /// A mouse wheel scroll event
 ///
 /// The scroll distance is expressed in "lines", an arbitrary unit
@@ -388,19 +1071,33 @@ impl From<p::PuglEventMotion> for EventContext {
 /// support finer resolution and/or higher values for fast scrolls, so
 /// programs should handle any value gracefully.
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scroll {
     /// horizontal scroll distance
     pub dx: f64,
     /// vertical scroll distance
     pub dy: f64,
-    pub modifiers: Modifiers
+    pub modifiers: Modifiers,
+    /// The direction of a discrete scroll, or `ScrollDirection::Smooth`
+    pub direction: ScrollDirection,
+    /// The phase of the scroll gesture the event belongs to
+    pub phase: ScrollPhase
+}
+
+impl Scroll {
+    /// Returns `true` for smooth scroll events, e.g. from a touchpad
+    pub fn is_smooth(&self) -> bool {
+        self.direction == ScrollDirection::Smooth
+    }
 }
 
 impl From<p::PuglEventScroll> for Scroll {
     fn from (se: p::PuglEventScroll) -> Scroll {
         Scroll {
             dx: se.dx, dy: se.dy,
-            modifiers: Modifiers::from_bits_truncate(se.state)
+            modifiers: Modifiers::from_bits_truncate(se.state),
+            direction: ScrollDirection::from(se.direction),
+            phase: ScrollPhase::None
         }
     }
 }
@@ -410,7 +1107,8 @@ impl From<p::PuglEventScroll> for EventContext {
         EventContext {
             pos: Coord { x: se.x, y: se.y },
             pos_root: Coord { x: se.xRoot, y: se.yRoot },
-            time: se.time
+            time: se.time,
+            flags: EventFlags::from_bits_truncate(se.flags)
         }
     }
 }
@@ -420,7 +1118,8 @@ impl From<p::PuglEventCrossing> for EventContext {
         EventContext {
             pos: Coord { x: pce.x, y: pce.y },
             pos_root: Coord { x: pce.xRoot, y: pce.yRoot },
-            time: pce.time
+            time: pce.time,
+            flags: EventFlags::from_bits_truncate(pce.flags)
         }
     }
 }
@@ -433,6 +1132,7 @@ impl From<p::PuglEventConfigure> for Size {
 
 /// The area that needs to be redrawn due to an expose event
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExposeArea {
     /// The view relative coordinate
     pub pos: Coord,
@@ -449,21 +1149,46 @@ impl From<p::PuglEventExpose> for ExposeArea {
     }
 }
 
+/// The reason of the pointer entering or leaving the view
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CrossingMode {
+    /// The pointer has been moved
+    Normal,
+    /// The pointer has been grabbed, e.g. by another window
+    Grab,
+    /// A pointer grab has been released
+    Ungrab
+}
+
+impl From<p::PuglCrossingMode> for CrossingMode {
+    fn from(cm: p::PuglCrossingMode) -> CrossingMode {
+        match cm {
+            p::PuglCrossingMode_PUGL_CROSSING_GRAB => CrossingMode::Grab,
+            p::PuglCrossingMode_PUGL_CROSSING_UNGRAB => CrossingMode::Ungrab,
+            _ => CrossingMode::Normal
+        }
+    }
+}
+
 /// Event types
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventType {
     KeyPress(Key),
     KeyRelease(Key),
     MouseButtonPress(MouseButton),
     MouseButtonRelease(MouseButton),
     MouseMove(MotionContext),
-    PointerIn,
-    PointerOut,
+    RelativeMove(RelativeMotion),
+    PointerIn(CrossingMode),
+    PointerOut(CrossingMode),
     Scroll(Scroll)
 }
 
 /// An event signaled by the windowing system
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Event {
     pub data: EventType,
     pub context: EventContext
@@ -500,7 +1225,8 @@ impl Event {
 }
 
 /// Available mouse cursors
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cursor {
     Arrow,
     Caret,
@@ -531,6 +1257,125 @@ impl Default for Cursor {
     }
 }
 
+bitflags! {
+    /// States of a top level window managed by the window manager
+    #[derive(Default)]
+    pub struct WindowState: u32 {
+        const NONE       = 0;
+        const MAXIMIZED  = 1;
+        const MINIMIZED  = 2;
+        const FULLSCREEN = 4;
+        /// Kept above other windows
+        const ABOVE      = 8;
+        /// Kept below other windows
+        const BELOW      = 16;
+    }
+}
+
+/// An image to be used as the icon of a top level window
+///
+/// The pixels are stored as non premultiplied 32 bit ARGB values row
+/// by row starting at the top left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowIcon {
+    width: u32,
+    height: u32,
+    pixels: Vec<u32>
+}
+
+impl WindowIcon {
+    /// Creates a `WindowIcon` from RGBA data
+    ///
+    /// `rgba` holds four bytes (red, green, blue, alpha) per pixel
+    /// row by row starting at the top left corner.
+    ///
+    /// Returns `None` if the length of `rgba` does not match `width`
+    /// and `height`.
+    ///
+    /// ```
+    /// let icon = pugl_sys::WindowIcon::from_rgba(1, 1, &[0xff, 0x80, 0x00, 0xff]).unwrap();
+    /// assert_eq!(icon.pixels(), &[0xffff8000]);
+    ///
+    /// assert!(pugl_sys::WindowIcon::from_rgba(2, 1, &[0xff, 0x80, 0x00, 0xff]).is_none());
+    /// ```
+    pub fn from_rgba(width: u32, height: u32, rgba: &[u8]) -> Option<WindowIcon> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        let pixels = rgba.chunks(4)
+            .map(|px| u32::from_be_bytes([px[3], px[0], px[1], px[2]]))
+            .collect();
+        Some(WindowIcon { width, height, pixels })
+    }
+
+    /// Creates a `WindowIcon` from a `cairo::ImageSurface`
+    ///
+    /// Only surfaces of the formats `ARgb32` and `Rgb24` are
+    /// supported, for all others `None` is returned.
+    pub fn from_surface(surface: &cairo::ImageSurface) -> Option<WindowIcon> {
+        let pixels = surface_pixels(surface)?;
+        Some(WindowIcon {
+            width: surface.get_width() as u32,
+            height: surface.get_height() as u32,
+            pixels
+        })
+    }
+
+    /// The width of the icon in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the icon in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The ARGB pixels of the icon
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+}
+
+/// Returns the ARGB pixels of `surface` row by row
+///
+/// Only surfaces of the formats `ARgb32` and `Rgb24` are supported,
+/// for all others `None` is returned.
+pub(crate) fn surface_pixels(surface: &cairo::ImageSurface) -> Option<Vec<u32>> {
+    let has_alpha = match surface.get_format() {
+        cairo::Format::ARgb32 => true,
+        cairo::Format::Rgb24 => false,
+        _ => return None
+    };
+    let width = surface.get_width() as usize;
+    let height = surface.get_height() as usize;
+    let stride = surface.get_stride() as usize;
+
+    let mut pixels = Vec::with_capacity(width * height);
+    surface.with_data(|data| {
+        for row in data.chunks(stride).take(height) {
+            for px in row.chunks(4).take(width) {
+                let px = u32::from_ne_bytes([px[0], px[1], px[2], px[3]]);
+                pixels.push(if has_alpha {
+                    unpremultiply(px)
+                } else {
+                    px | 0xff00_0000
+                });
+            }
+        }
+    }).ok()?;
+    Some(pixels)
+}
+
+fn unpremultiply(argb: u32) -> u32 {
+    let alpha = argb >> 24;
+    if alpha == 0 {
+        return 0;
+    }
+    let channel = |shift: u32| (((argb >> shift) & 0xff) * 255 + alpha / 2) / alpha;
+    alpha << 24 | channel(16) << 16 | channel(8) << 8 | channel(0)
+}
+
 
 /// Return status code.
 #[repr(u32)]
@@ -670,6 +1515,75 @@ mod test {
         assert_eq!(s.h, 7.);
     }
 
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Rect {
+        Rect { pos: Coord { x, y }, size: Size { w, h } }
+    }
+
+    #[test]
+    fn rect_intersection() {
+        let r = rect(0., 0., 100., 50.);
+        assert_eq!(r.intersection(&rect(50., 25., 100., 100.)), Some(rect(50., 25., 50., 25.)));
+        assert_eq!(r.intersection(&rect(10., 10., 10., 10.)), Some(rect(10., 10., 10., 10.)));
+        assert_eq!(r.intersection(&rect(100., 0., 10., 10.)), None);
+        assert_eq!(r.intersection(&rect(-20., 60., 10., 10.)), None);
+    }
+
+    #[test]
+    fn rect_union() {
+        let r = rect(0., 0., 100., 50.);
+        assert_eq!(r.union(&rect(50., 25., 100., 100.)), rect(0., 0., 150., 125.));
+        assert_eq!(r.union(&rect(10., 10., 10., 10.)), r);
+        assert_eq!(r.union(&rect(-20., 60., 10., 10.)), rect(-20., 0., 120., 70.));
+    }
+
+    #[test]
+    fn rect_centered_in() {
+        let r = rect(0., 0., 100., 50.);
+        assert_eq!(r.centered_in(&rect(100., 200., 300., 150.)), rect(200., 250., 100., 50.));
+    }
+
+    #[test]
+    fn rect_clamped_to() {
+        let area = rect(0., 0., 800., 600.);
+        assert_eq!(rect(10., 20., 100., 50.).clamped_to(&area), rect(10., 20., 100., 50.));
+        assert_eq!(rect(750., -20., 100., 50.).clamped_to(&area), rect(700., 0., 100., 50.));
+        assert_eq!(rect(-30., 580., 100., 50.).clamped_to(&area), rect(0., 550., 100., 50.));
+        assert_eq!(rect(50., 50., 1000., 50.).clamped_to(&area), rect(0., 50., 1000., 50.));
+    }
+
+    fn two_monitors() -> Vec<Monitor> {
+        vec![
+            Monitor { name: "left".to_string(), geometry: rect(0., 0., 1920., 1080.), primary: false },
+            Monitor { name: "right".to_string(), geometry: rect(1920., 0., 1280., 1024.), primary: true }
+        ]
+    }
+
+    #[test]
+    fn monitor_find_for_overlap() {
+        let monitors = two_monitors();
+        let monitor = Monitor::find_for(&monitors, &rect(1800., 100., 400., 300.)).unwrap();
+        assert_eq!(monitor.name, "right");
+        let monitor = Monitor::find_for(&monitors, &rect(1700., 100., 400., 300.)).unwrap();
+        assert_eq!(monitor.name, "left");
+    }
+
+    #[test]
+    fn monitor_find_for_offscreen() {
+        let monitors = two_monitors();
+        let monitor = Monitor::find_for(&monitors, &rect(5000., 100., 400., 300.)).unwrap();
+        assert_eq!(monitor.name, "right");
+        assert!(Monitor::find_for(&[], &rect(5000., 100., 400., 300.)).is_none());
+    }
+
+    #[test]
+    fn monitor_place_frame() {
+        let monitors = two_monitors();
+        assert_eq!(Monitor::place_frame(&monitors, rect(100., 100., 400., 300.)), rect(100., 100., 400., 300.));
+        assert_eq!(Monitor::place_frame(&monitors, rect(3000., 900., 400., 300.)), rect(2800., 724., 400., 300.));
+        assert_eq!(Monitor::place_frame(&monitors, rect(4000., 900., 400., 300.)), rect(2360., 362., 400., 300.));
+        assert_eq!(Monitor::place_frame(&[], rect(4000., 900., 400., 300.)), rect(4000., 900., 400., 300.));
+    }
+
     #[test]
     fn from_pugl_rect_to_rect() {
         let pr = p::PuglRect {
@@ -759,6 +1673,7 @@ mod test {
     fn from_pugl_key_to_key() {
         let key = Key::from(pugl_event_key_press_small_a());
         assert_eq!(key.modifiers, Modifiers::from_bits_truncate(0));
+        assert!(!key.is_repeat);
         let key = Key::from(pugl_event_key_release_capital_a());
         assert_eq!(key.modifiers, Modifiers::from_bits_truncate(1));
         let key = Key::from(pugl_event_key_press_f1());
@@ -814,7 +1729,41 @@ mod test {
             (p::PuglKey_PUGL_KEY_SCROLL_LOCK, SpecialKey::KeyScrollLock),
             (p::PuglKey_PUGL_KEY_NUM_LOCK, SpecialKey::KeyNumLock),
             (p::PuglKey_PUGL_KEY_PRINT_SCREEN, SpecialKey::KeyPrintScreen),
-            (p::PuglKey_PUGL_KEY_PAUSE, SpecialKey::KeyPause)
+            (p::PuglKey_PUGL_KEY_PAUSE, SpecialKey::KeyPause),
+            (p::PuglKey_PUGL_KEY_SHIFT, SpecialKey::ShiftL),
+            (p::PuglKey_PUGL_KEY_CTRL, SpecialKey::CtrlL),
+            (p::PuglKey_PUGL_KEY_ALT, SpecialKey::AltL),
+            (p::PuglKey_PUGL_KEY_SUPER, SpecialKey::SuperL)
+        ]
+    }
+
+    /// X11 key codes of the keypad and media keys, the key values
+    /// pugl reports with NumLock on and off, and the expected key
+    fn keypad_and_media_tuples() -> Vec<(u32, p::PuglKey, p::PuglKey, SpecialKey)> {
+        vec![
+            (90, '0' as u32, p::PuglKey_PUGL_KEY_INSERT, SpecialKey::Keypad0),
+            (87, '1' as u32, p::PuglKey_PUGL_KEY_END, SpecialKey::Keypad1),
+            (88, '2' as u32, p::PuglKey_PUGL_KEY_DOWN, SpecialKey::Keypad2),
+            (89, '3' as u32, p::PuglKey_PUGL_KEY_PAGE_DOWN, SpecialKey::Keypad3),
+            (83, '4' as u32, p::PuglKey_PUGL_KEY_LEFT, SpecialKey::Keypad4),
+            (84, '5' as u32, 0, SpecialKey::Keypad5),
+            (85, '6' as u32, p::PuglKey_PUGL_KEY_RIGHT, SpecialKey::Keypad6),
+            (79, '7' as u32, p::PuglKey_PUGL_KEY_HOME, SpecialKey::Keypad7),
+            (80, '8' as u32, p::PuglKey_PUGL_KEY_UP, SpecialKey::Keypad8),
+            (81, '9' as u32, p::PuglKey_PUGL_KEY_PAGE_UP, SpecialKey::Keypad9),
+            (91, '.' as u32, p::PuglKey_PUGL_KEY_DELETE, SpecialKey::KeypadDecimal),
+            (104, '\r' as u32, '\r' as u32, SpecialKey::KeypadEnter),
+            (86, '+' as u32, '+' as u32, SpecialKey::KeypadAdd),
+            (82, '-' as u32, '-' as u32, SpecialKey::KeypadSubtract),
+            (63, '*' as u32, '*' as u32, SpecialKey::KeypadMultiply),
+            (106, '/' as u32, '/' as u32, SpecialKey::KeypadDivide),
+            (121, 0, 0, SpecialKey::VolumeMute),
+            (122, 0, 0, SpecialKey::VolumeDown),
+            (123, 0, 0, SpecialKey::VolumeUp),
+            (172, 0, 0, SpecialKey::MediaPlayPause),
+            (174, 0, 0, SpecialKey::MediaStop),
+            (173, 0, 0, SpecialKey::MediaPrevious),
+            (171, 0, 0, SpecialKey::MediaNext)
         ]
     }
 
@@ -828,14 +1777,90 @@ mod test {
 
     #[test]
     fn from_special_key_to_special_key_no_special_key() {
-        assert!(SpecialKey::from(42) == SpecialKey::None)
+        assert!(SpecialKey::from(42) == SpecialKey::None);
+        assert!(SpecialKey::from(0) == SpecialKey::None);
+    }
+
+    #[test]
+    fn special_key_from_pugl_key() {
+        for (pk, sk) in key_tuples() {
+            assert_eq!(SpecialKey::from_pugl_key(pk), Some(sk), "{}", pk);
+        }
+        assert_eq!(SpecialKey::from_pugl_key(42), None);
+        assert_eq!(SpecialKey::from_pugl_key('a' as u32), None);
+        assert_eq!(SpecialKey::from_pugl_key(0), None);
+    }
+
+    #[test]
+    fn from_pugl_key_to_keyval_special_keys() {
+        // 38 is the key code of A, which is neither on the keypad nor a media key
+        for (pk, sk) in key_tuples() {
+            assert_eq!(KeyVal::from(pugl_event_key(pk, 38)), KeyVal::Special(sk), "{}", pk);
+        }
+    }
+
+    #[test]
+    fn from_pugl_key_to_keyval_keypad_and_media_keys() {
+        for (keycode, num_lock_on, num_lock_off, sk) in keypad_and_media_tuples() {
+            assert_eq!(KeyVal::from(pugl_event_key(num_lock_on, keycode)), KeyVal::Special(sk), "{}", keycode);
+            assert_eq!(KeyVal::from(pugl_event_key(num_lock_off, keycode)), KeyVal::Special(sk), "{}", keycode);
+        }
+    }
+
+    fn pugl_event_key(key: p::PuglKey, keycode: u32) -> p::PuglEventKey {
+        p::PuglEventKey {
+            key,
+            keycode,
+            ..pugl_event_key_press_small_a()
+        }
+    }
+
+    #[test]
+    fn physical_key_from_keycode() {
+        assert_eq!(PhysicalKey::from_keycode(24), PhysicalKey::KeyQ);
+        assert_eq!(PhysicalKey::from_keycode(38), PhysicalKey::KeyA);
+        assert_eq!(PhysicalKey::from_keycode(10), PhysicalKey::Digit1);
+        assert_eq!(PhysicalKey::from_keycode(65), PhysicalKey::Space);
+        assert_eq!(PhysicalKey::from_keycode(87), PhysicalKey::Numpad1);
+        assert_eq!(PhysicalKey::from_keycode(3), PhysicalKey::Unknown(3));
+        assert_eq!(PhysicalKey::from_keycode(255), PhysicalKey::Unknown(255));
+    }
+
+    #[test]
+    fn physical_key_roundtrip() {
+        for code in 0..256 {
+            assert_eq!(PhysicalKey::from_keycode(code).keycode(), code);
+        }
+    }
+
+    #[test]
+    fn from_pugl_key_to_keyval_invalid_char() {
+        let kv = KeyVal::from(pugl_event_key(0xD800, 38));
+        assert_eq!(kv, KeyVal::Unknown(0xD800));
+        let kv = KeyVal::from(pugl_event_key(0x110000, 38));
+        assert_eq!(kv, KeyVal::Unknown(0x110000));
+    }
+
+    #[test]
+    fn from_pugl_key_to_keyval_by_keycode() {
+        let kv = KeyVal::from(pugl_event_key(0, 87));
+        assert_eq!(kv, KeyVal::Special(SpecialKey::Keypad1));
+        let kv = KeyVal::from(pugl_event_key(0, 172));
+        assert_eq!(kv, KeyVal::Special(SpecialKey::MediaPlayPause));
+        let kv = KeyVal::from(pugl_event_key(0, 255));
+        assert_eq!(kv, KeyVal::Unknown(0));
+        // the keycode takes precedence over the key value
+        let kv = KeyVal::from(pugl_event_key(0x2b, 86));
+        assert_eq!(kv, KeyVal::Special(SpecialKey::KeypadAdd));
+        let kv = KeyVal::from(pugl_event_key(0x2b, 21));
+        assert_eq!(kv, KeyVal::Character('+'));
     }
 
     #[test]
     fn from_pugl_crossing_to_event_context() {
         let pev_crossing = p::PuglEventCrossing {
             type_: p::PuglEventType_PUGL_POINTER_IN,
-            flags: 0,
+            flags: p::PuglEventFlag_PUGL_IS_SEND_EVENT,
             time: 2.0,
             x: 23.0,
             y: 42.0,
@@ -850,6 +1875,14 @@ mod test {
         assert_eq!(ec.pos_root.x, 123.0);
         assert_eq!(ec.pos_root.y, 142.0);
         assert_eq!(ec.time, 2.0);
+        assert_eq!(ec.flags, EventFlags::IS_SEND_EVENT);
+    }
+
+    #[test]
+    fn from_pugl_crossing_mode() {
+        assert_eq!(CrossingMode::from(p::PuglCrossingMode_PUGL_CROSSING_NORMAL), CrossingMode::Normal);
+        assert_eq!(CrossingMode::from(p::PuglCrossingMode_PUGL_CROSSING_GRAB), CrossingMode::Grab);
+        assert_eq!(CrossingMode::from(p::PuglCrossingMode_PUGL_CROSSING_UNGRAB), CrossingMode::Ungrab);
     }
 
     fn pugl_mouse_button() ->  p::PuglEventButton {
@@ -870,7 +1903,24 @@ mod test {
     fn from_pugl_button_to_mouse_button() {
         let mb = MouseButton::from(pugl_mouse_button());
         assert_eq!(mb.modifiers.bits, 2);
-        assert_eq!(mb.num, 1);
+        assert_eq!(mb.button, Button::Left);
+        assert_eq!(mb.click_count, 1);
+    }
+
+    #[test]
+    fn button_numbers() {
+        for &num in [1, 2, 3, 8, 9, 4, 10].iter() {
+            assert_eq!(Button::from(num).number(), num);
+        }
+        assert_eq!(Button::from(8), Button::Back);
+        assert_eq!(Button::from(9), Button::Forward);
+        assert_eq!(Button::from(10), Button::Other(10));
+    }
+
+    #[test]
+    fn mouse_buttons_from_button() {
+        assert_eq!(MouseButtons::from(Button::Middle), MouseButtons::MIDDLE);
+        assert_eq!(MouseButtons::from(Button::Other(12)), MouseButtons::NONE);
     }
 
     #[test]
@@ -899,7 +1949,11 @@ mod test {
     #[test]
     fn from_pugl_motion_to_motion_context() {
         let mc = MotionContext::from(pugl_event_motion());
-        assert_eq!(mc, MotionContext { modifiers: Modifiers::from_bits_truncate(2), flags: EventFlags::NONE });
+        assert_eq!(mc, MotionContext {
+            modifiers: Modifiers::from_bits_truncate(2),
+            buttons: MouseButtons::NONE,
+            flags: EventFlags::NONE
+        });
     }
 
     #[test]
@@ -934,8 +1988,24 @@ mod test {
         assert_eq!(sc, Scroll {
             dx: 3.14,
             dy: 2.71,
-            modifiers: Modifiers::from_bits_truncate(2)
+            modifiers: Modifiers::from_bits_truncate(2),
+            direction: ScrollDirection::Up,
+            phase: ScrollPhase::None
+        });
+    }
+
+    #[test]
+    fn from_pugl_scroll_direction() {
+        assert_eq!(ScrollDirection::from(p::PuglScrollDirection_PUGL_SCROLL_UP), ScrollDirection::Up);
+        assert_eq!(ScrollDirection::from(p::PuglScrollDirection_PUGL_SCROLL_DOWN), ScrollDirection::Down);
+        assert_eq!(ScrollDirection::from(p::PuglScrollDirection_PUGL_SCROLL_LEFT), ScrollDirection::Left);
+        assert_eq!(ScrollDirection::from(p::PuglScrollDirection_PUGL_SCROLL_RIGHT), ScrollDirection::Right);
+        assert_eq!(ScrollDirection::from(p::PuglScrollDirection_PUGL_SCROLL_SMOOTH), ScrollDirection::Smooth);
+        let sc = Scroll::from(p::PuglEventScroll {
+            direction: p::PuglScrollDirection_PUGL_SCROLL_SMOOTH,
+            ..pugl_scroll_event()
         });
+        assert!(sc.is_smooth());
     }
 
     #[test]
@@ -993,6 +2063,56 @@ mod test {
         assert!(EventFlags::from_bits_truncate(is_both).contains(EventFlags::IS_HINT));
     }
 
+    #[test]
+    fn window_icon_from_rgba() {
+        let rgba = [
+            0x10, 0x20, 0x30, 0xff,  0x40, 0x50, 0x60, 0x80,
+            0x70, 0x80, 0x90, 0x00,  0xa0, 0xb0, 0xc0, 0x01
+        ];
+        let icon = WindowIcon::from_rgba(2, 2, &rgba).unwrap();
+        assert_eq!(icon.width(), 2);
+        assert_eq!(icon.height(), 2);
+        assert_eq!(icon.pixels(), &[0xff102030, 0x80405060, 0x00708090, 0x01a0b0c0]);
+    }
+
+    #[test]
+    fn window_icon_from_rgba_bad_length() {
+        assert!(WindowIcon::from_rgba(2, 2, &[0; 12]).is_none());
+    }
+
+    #[test]
+    fn window_icon_from_argb_surface() {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 3, 2).unwrap();
+        {
+            let cr = cairo::Context::new(&surface);
+            cr.set_source_rgba(1.0, 0.0, 0.0, 0.5);
+            cr.paint();
+        }
+        let icon = WindowIcon::from_surface(&surface).unwrap();
+        assert_eq!(icon.width(), 3);
+        assert_eq!(icon.height(), 2);
+        assert_eq!(icon.pixels().len(), 6);
+        assert!(icon.pixels().iter().all(|&px| px == 0x80ff0000));
+    }
+
+    #[test]
+    fn window_icon_from_rgb_surface() {
+        let surface = cairo::ImageSurface::create(cairo::Format::Rgb24, 1, 1).unwrap();
+        {
+            let cr = cairo::Context::new(&surface);
+            cr.set_source_rgb(0.0, 0.0, 1.0);
+            cr.paint();
+        }
+        let icon = WindowIcon::from_surface(&surface).unwrap();
+        assert_eq!(icon.pixels(), &[0xff0000ff]);
+    }
+
+    #[test]
+    fn window_icon_from_a8_surface() {
+        let surface = cairo::ImageSurface::create(cairo::Format::A8, 1, 1).unwrap();
+        assert!(WindowIcon::from_surface(&surface).is_none());
+    }
+
     #[test]
     fn from_pugl_view_hint_bool_value_true() {
         let pugl_view_hint_true = p::PuglViewHintValue_PUGL_TRUE;
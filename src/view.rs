@@ -1,12 +1,28 @@
+#[cfg(feature = "serde")]
+use std::io;
 use std::marker::PhantomData;
 
 use crate::types::*;
 use crate::pugl as p;
+use crate::native;
+use crate::input::InputState;
+use crate::shortcut::{Shortcut, ShortcutMap};
+use crate::record::{Record, RecordedEvent};
+#[cfg(feature = "serde")]
+use crate::record::Recorder;
+use crate::backend::{BackendImpl, DeferredCall, ViewEvent};
+pub use crate::backend::Backend;
 
 use mockall_double::double;
 #[double] use crate::pugl::pffi;
 
-pub type PuglViewFFI = *mut p::PuglView;
+/// A handle to a view
+///
+/// The handle is passed to the closure creating the UI object by
+/// [`PuglView::new()`](struct.PuglView.html#method.new). The UI object
+/// needs to retain it to implement
+/// [`PuglViewTrait::view()`](trait.PuglViewTrait.html#tymethod.view).
+pub type PuglViewFFI = *mut ViewInstance;
 
 /// The central trait for an object of a pugl "UI"
 ///
@@ -16,7 +32,9 @@ pub type PuglViewFFI = *mut p::PuglView;
 /// `PuglViewTrait`. The provided methods
 /// [`focus_in()`](#method.focus_in) and
 /// [`focus_out()`](#method.focus_out) as wellas
-/// [`timer_event()`](#method.timer_event] can be implmentat
+/// [`timer_event()`](#method.timer_event],
+/// [`shortcut_event()`](#method.shortcut_event) and
+/// [`window_state_changed()`](#method.window_state_changed) can be implmentat
 /// optionally.
 /// All the other provided methods should not be reimplemented.
 pub trait PuglViewTrait {
@@ -74,12 +92,39 @@ pub trait PuglViewTrait {
     /// Shall return a result Status.
     fn timer_event(&mut self, _id: usize) -> Status { Status::Success }
 
+    /// Called when a shortcut added by
+    /// [`add_shortcut()`](#method.add_shortcut) has been pressed.
+    ///
+    /// The key press of the shortcut is not passed to
+    /// [`event()`](#method.event).
+    ///
+    /// Should be reimplemented if the application at some point calls
+    /// [`add_shortcut()`](#method.add_shortcut)
+    ///
+    /// Shall return a result Status.
+    fn shortcut_event(&mut self, _id: usize) -> Status { Status::Success }
+
+    /// Called when the window manager changed the state of the window
+    ///
+    /// Should be reimplemented if the application needs to react on
+    /// the window being maximized, minimized and so on, for example to
+    /// update its own controls.
+    ///
+    /// Changes made by the window manager alone are noticed when
+    /// [`update()`](#method.update) returns. As they do not wake up
+    /// the event loop, with a blocking `update(-1.0)` they are only
+    /// reported along with the next other event. Applications that
+    /// need them promptly should call `update()` with a timeout.
+    ///
+    /// Shall return a result Status.
+    fn window_state_changed(&mut self, _state: WindowState) -> Status { Status::Success }
+
     /// Returns a handle to the window system's view
     fn view (&self) -> PuglViewFFI;
 
     /// Returns a pointer to the `PugleWorld`
     fn world (&self) -> *mut p::PuglWorld {
-        unsafe { pffi::puglGetWorld(self.view() as PuglViewFFI) }
+        with_instance(self.view(), |instance| instance.backend.world())
     }
 
     /// Request a redisplay for the entire view.
@@ -91,7 +136,7 @@ pub trait PuglViewTrait {
     /// an expose will be enqueued to be processed in the next event
     /// loop iteration.
     fn post_redisplay (&self) -> Status {
-        unsafe { Status::from(pffi::puglPostRedisplay(self.view())) }
+        with_instance(self.view(), |instance| instance.backend.post_redisplay())
     }
 
     /// Request a redisplay of the given rectangle within the view.
@@ -100,27 +145,64 @@ pub trait PuglViewTrait {
     /// but allows giving a precise region for redrawing only a
     /// portion of the view.
     fn post_redisplay_rect(&self, pos: Coord, size: Size) -> Status {
-        let p_rect = p::PuglRect {
-            x: pos.x,
-            y: pos.y,
-            width: size.w,
-            height: size.h
-        };
-        unsafe { Status::from(pffi::puglPostRedisplayRect(self.view(), p_rect)) }
+        with_instance(self.view(), |instance| instance.backend.post_redisplay_rect(Rect { pos, size }))
     }
 
     ///  Get the current position and size of the view.
     ///
     ///  The position is in screen coordinates with an upper left origin.
     fn get_frame(&self) -> Rect {
-        unsafe { pffi::puglGetFrame(self.view()).into() }
+        with_instance(self.view(), |instance| instance.backend.frame())
     }
 
     /// Set the current position and size of the view.
     ///
     /// The position is in screen coordinates with an upper left origin.
     fn set_frame (&self, frame: Rect) -> Status {
-        unsafe { Status::from(pffi::puglSetFrame(self.view(), frame.into())) }
+        with_instance(self.view(), |instance| instance.backend.set_frame(frame))
+    }
+
+    /// Returns the monitors connected to the screen
+    ///
+    /// Returns an empty `Vec` if the monitor setup cannot be determined.
+    fn monitors(&self) -> Vec<Monitor> {
+        with_instance(self.view(), |instance| instance.backend.monitors())
+    }
+
+    /// Centers the view on the monitor it is on
+    ///
+    /// If the view is on none of the monitors, it is centered on the
+    /// primary monitor. As the size of the view needs to be known,
+    /// this should be called after [`realize()`](#method.realize).
+    fn center_on_screen(&self) -> Status {
+        match Monitor::find_for(&self.monitors(), &self.get_frame()) {
+            Some(monitor) => self.center_on(monitor.geometry),
+            None => Status::Failure
+        }
+    }
+
+    /// Centers the view on `area`
+    ///
+    /// To center a dialog on its parent window, the frame of the
+    /// parent view, as returned by its [`get_frame()`](#method.get_frame)
+    /// can be used as `area`. As the size of the view needs to be
+    /// known, this should be called after [`realize()`](#method.realize).
+    fn center_on(&self, area: Rect) -> Status {
+        let frame = self.get_frame();
+        if frame.size.w <= 0. || frame.size.h <= 0. {
+            return Status::Failure;
+        }
+        self.set_frame(frame.centered_in(&area))
+    }
+
+    /// Restores a previously saved frame of the view
+    ///
+    /// Unlike [`set_frame()`](#method.set_frame) this makes sure that
+    /// the view ends up visible on one of the monitors, even if the
+    /// monitor setup has changed since the frame was saved. See
+    /// [`Monitor::place_frame()`](struct.Monitor.html#method.place_frame).
+    fn restore_frame(&self, frame: Rect) -> Status {
+        self.set_frame(Monitor::place_frame(&self.monitors(), frame))
     }
 
     /// Set the default size of the view.
@@ -130,7 +212,7 @@ pub trait PuglViewTrait {
     /// the view, which will be the initial size of the window if this
     /// is a top level view.
     fn set_default_size(&self, width: i32, height: i32) -> Status {
-        unsafe { Status::from(pffi::puglSetDefaultSize(self.view(), width, height)) }
+        with_instance(self.view(), |instance| instance.backend.set_default_size(width, height))
     }
 
     /// Set the minimum size of the view.
@@ -139,7 +221,7 @@ pub trait PuglViewTrait {
     /// before [`realize()`](#method.realize) and [`show_window()`](#method.show_window)
     /// to avoid stutter, though it can be called afterwards as well.
     fn set_min_size (&self, width: i32, height: i32) -> Status {
-        unsafe { Status::from(pffi::puglSetMinSize(self.view(), width, height)) }
+        with_instance(self.view(), |instance| instance.backend.set_min_size(width, height))
     }
 
     /// Set the maximum size of the view.
@@ -148,7 +230,7 @@ pub trait PuglViewTrait {
     /// before [`realize()`](#method.realize) and [`show_window()`](#method.show_window) to
     /// avoid stutter, though it can be called afterwards as well.
     fn set_max_size (&self, width: i32, height: i32) -> Status {
-        unsafe { Status::from(pffi::puglSetMaxSize(self.view(), width, height)) }
+        with_instance(self.view(), |instance| instance.backend.set_max_size(width, height))
     }
 
     /// Set the view aspect ratio range.
@@ -164,99 +246,76 @@ pub trait PuglViewTrait {
     /// before [`realize()`](#method.realize) and [`show_window()`](#method.show_window) to avoid stutter,
     /// though it can be called afterwards as well
     fn set_aspect_ratio(&self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Status {
-        unsafe { Status::from(pffi::puglSetAspectRatio(self.view(), min_x, min_y, max_x, max_y)) }
+        with_instance(self.view(), |instance| instance.backend.set_aspect_ratio(min_x, min_y, max_x, max_y))
     }
 
     /// Returns true iff the window is resizable
     fn is_resizable(&self) -> bool {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_RESIZABLE) != 0
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_RESIZABLE) != 0
     }
 
     /// Make the view resizable.
     ///
     /// This should be called before [[`show_window()`](#method.show_window)](#method.show_window) and [`realize()`](#method.realize).
     fn make_resizable(&self) -> Status {
-        unsafe {
-            Status::from(pffi::puglSetViewHint(
-                self.view(),
-                p::PuglViewHint_PUGL_RESIZABLE, p::PuglViewHintValue_PUGL_TRUE))
-        }
+        set_view_hint(self.view(), p::PuglViewHint_PUGL_RESIZABLE, p::PuglViewHintValue_PUGL_TRUE)
     }
 
     /// Returns a [`ViewHintBool`](enum.ViewHintBool.html) whether the view is ignoring
     /// key repeats.
     fn is_ignoring_key_repeats(&self) -> ViewHintBool {
-        unsafe {
-            ViewHintBool::from(pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT))
-        }
+        ViewHintBool::from(view_hint(self.view(), p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT))
     }
 
     /// Gives the view the hint whether it should ignore key repeats.
+    ///
+    /// If key repeats are not ignored, they are reported as
+    /// `EventType::KeyPress` with
+    /// [`Key::is_repeat`](struct.Key.html#structfield.is_repeat) set.
+    /// Key repeats never trigger shortcuts, see
+    /// [`add_shortcut()`](#method.add_shortcut).
     fn set_ignore_key_repeats(&self, value: ViewHintBool) -> Status {
-        unsafe {
-            Status::from(pffi::puglSetViewHint(
-                self.view(),
-                p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT,
-                p::PuglViewHintValue::from(value)))
-        }
+        set_view_hint(self.view(), p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT, p::PuglViewHintValue::from(value))
     }
 
     /// Returns the number of bits for the red channel of the view
     fn red_bits(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_RED_BITS) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_RED_BITS) as u32
     }
 
     /// Returns the number of bits for the green channel of the view
     fn green_bits(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_GREEN_BITS) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_GREEN_BITS) as u32
     }
 
     /// Returns the number of bits for the blue channel of the view
     fn blue_bits(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_BLUE_BITS) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_BLUE_BITS) as u32
     }
 
     /// Returns the number of bits for the alpha channel of the view
     fn alpha_bits(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_ALPHA_BITS) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_ALPHA_BITS) as u32
     }
 
     /// Returns the number of bits for the depth buffer of the view
     fn depth_bits(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_DEPTH_BITS) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_DEPTH_BITS) as u32
     }
 
     /// Returns the number of bits for the stencil buffer of the view
     fn stencil_bits(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_STENCIL_BITS) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_STENCIL_BITS) as u32
     }
 
     /// Returns the number of samples per pixel
     fn samples(&self) -> u32 {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_SAMPLES) as u32
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_SAMPLES) as u32
     }
 
     /// Returns true iff double buffering should be used
     fn double_buffer(&self) -> bool {
-        unsafe {
-            pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_DOUBLE_BUFFER) == 1
-        }
+        view_hint(self.view(), p::PuglViewHint_PUGL_DOUBLE_BUFFER) == 1
     }
 
     /// Sets whether double buffering should be used
@@ -266,33 +325,61 @@ pub trait PuglViewTrait {
         } else {
             0
         };
-        unsafe {
-            Status::from(pffi::puglSetViewHint(self.view(), p::PuglViewHint_PUGL_DOUBLE_BUFFER, v))
-        }
+        set_view_hint(self.view(), p::PuglViewHint_PUGL_DOUBLE_BUFFER, v)
     }
 
     /// Returns number of frames between buffer swaps
     fn swap_interval(&self) -> ViewHintInt {
-        unsafe {
-            ViewHintInt::from(pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_SWAP_INTERVAL))
-        }
+        ViewHintInt::from(view_hint(self.view(), p::PuglViewHint_PUGL_SWAP_INTERVAL))
     }
 
     /// Returns the refresh rate in Hz
     fn refresh_rate(&self) -> ViewHintInt {
-        unsafe {
-            ViewHintInt::from(pffi::puglGetViewHint(self.view(), p::PuglViewHint_PUGL_REFRESH_RATE))
-        }
+        ViewHintInt::from(view_hint(self.view(), p::PuglViewHint_PUGL_REFRESH_RATE))
     }
 
     /// Sets the window title
     fn set_window_title(&self, title: &str) -> Status {
-        let title =
-            std::ffi::CString::new(title.as_bytes())
-                .expect("window title must not contain 0 bytes");
-        unsafe {
-            Status::from(pffi::puglSetWindowTitle(self.view(), title.into_raw()))
-        }
+        with_instance(self.view(), |instance| instance.backend.set_window_title(title))
+    }
+
+    /// Sets the icon of the window
+    ///
+    /// `icons` can hold the same icon in several resolutions, the
+    /// window manager picks the one that fits best.
+    ///
+    /// If the view is not yet realized, the icons are remembered and
+    /// set as soon as [`realize()`](#method.realize) is called.
+    fn set_window_icon(&self, icons: &[WindowIcon]) -> Status {
+        with_instance(self.view(), |instance| {
+            instance.state.window_icon = icons.to_vec();
+            instance.backend.set_window_icon(icons)
+        })
+    }
+
+    /// Requests the window manager to put the window into `state` or out of it
+    ///
+    /// `state` can be a combination of several states, which are all
+    /// enabled or disabled depending on `enable`. It is up to the
+    /// window manager to honor the request.
+    ///
+    /// If the view is not yet realized, the state is remembered and
+    /// applied as soon as [`realize()`](#method.realize) is called.
+    fn set_window_state(&self, state: WindowState, enable: bool) -> Status {
+        with_instance(self.view(), |instance| {
+            instance.state.requested_window_state.set(state, enable);
+            instance.backend.set_window_state(state, enable)
+        })
+    }
+
+    /// Returns the state of the window
+    ///
+    /// If the view is not yet realized, the state requested by
+    /// [`set_window_state()`](#method.set_window_state) is returned.
+    fn window_state(&self) -> WindowState {
+        with_instance(self.view(), |instance| {
+            instance.backend.window_state().unwrap_or(instance.state.requested_window_state)
+        })
     }
 
     /// Realize a view by creating a corresponding system view or window.
@@ -307,7 +394,7 @@ pub trait PuglViewTrait {
     /// The view should be fully configured using the above functions before this is
     /// called.  This function may only be called once per view.
     fn realize(&self) -> Status {
-        unsafe { Status::from(pffi::puglRealize(self.view())) }
+        with_backend(self.view(), |backend| backend.realize())
     }
 
     /// Show the view.
@@ -318,17 +405,76 @@ pub trait PuglViewTrait {
     /// If the view is currently hidden, it will be shown and possibly
     /// raised to the top depending on the platform.
     fn show_window(&self) -> Status {
-        unsafe { Status::from(pffi::puglShow(self.view())) }
+        with_backend(self.view(), |backend| backend.show_window())
     }
 
     /// Hide the current window
     fn hide_window(&self) -> Status {
-        unsafe { Status::from(pffi::puglHideWindow(self.view())) }
+        with_instance(self.view(), |instance| instance.backend.hide_window())
     }
 
     /// Return true iff the view is currently visible.
     fn is_visible(&self) -> bool {
-        unsafe { pffi::puglGetVisible(self.view()) }
+        with_instance(self.view(), |instance| instance.backend.is_visible())
+    }
+
+    /// Request user attention
+    ///
+    /// This hints to the system that the window or application
+    /// requires attention from the user. The exact effect depends on
+    /// the platform, but is usually something like a flashing task
+    /// bar entry or bouncing application icon.
+    fn request_attention(&self) -> Status {
+        with_instance(self.view(), |instance| instance.backend.request_attention())
+    }
+
+    /// Grabs the pointer
+    ///
+    /// While the pointer is grabbed, all the pointer events are
+    /// reported to the view, even if the pointer is outside of the
+    /// window. This is useful to keep track of drags that leave the
+    /// window, see [`DragTracker`](struct.DragTracker.html).
+    ///
+    /// Fails if the view is not realized or if the pointer is grabbed
+    /// by another window.
+    fn grab_pointer(&self) -> Status {
+        with_instance(self.view(), |instance| instance.backend.grab_pointer())
+    }
+
+    /// Releases a pointer grab by [`grab_pointer()`](#method.grab_pointer)
+    fn ungrab_pointer(&self) -> Status {
+        with_instance(self.view(), |instance| instance.backend.ungrab_pointer())
+    }
+
+    /// Moves the pointer to `pos` relative to the top left corner of the view
+    ///
+    /// Fails if the view is not realized.
+    fn set_pointer_position(&self, pos: Coord) -> Status {
+        with_instance(self.view(), |instance| instance.backend.set_pointer_position(pos))
+    }
+
+    /// Switches the relative pointer mode on or off
+    ///
+    /// In relative pointer mode the pointer is grabbed, the cursor is
+    /// hidden and the pointer is warped back to the position where it
+    /// was when the mode was switched on. Instead of
+    /// `EventType::MouseMove` the view receives
+    /// `EventType::RelativeMove` events carrying the distance the
+    /// pointer has moved. This is useful for endless knobs and
+    /// sliders. When the mode is switched off, the pointer is put back
+    /// to the start position, the grab is released and the cursor is
+    /// shown again. The mode is also switched off when the view loses
+    /// the focus.
+    ///
+    /// Fails if the view is not realized or if the pointer cannot be
+    /// grabbed.
+    fn set_relative_pointer_mode(&self, enable: bool) -> Status {
+        set_relative_pointer_mode(self.view(), enable)
+    }
+
+    /// Returns `true` if the view is in relative pointer mode
+    fn relative_pointer_mode(&self) -> bool {
+        with_instance(self.view(), |instance| instance.state.input.relative_anchor().is_some())
     }
 
     /// Set the mouse cursor.
@@ -338,7 +484,7 @@ pub trait PuglViewTrait {
     /// not supported on this system, for example if compiled on X11
     /// without Xcursor support.
     fn set_cursor(&self, c: Cursor) -> Status {
-        unsafe { Status::from(pffi::puglSetCursor(self.view(), c.into())) }
+        with_instance(self.view(), |instance| instance.backend.set_cursor(c))
     }
 
     /// Update by processing events from the window system.
@@ -366,7 +512,14 @@ pub trait PuglViewTrait {
     /// `Status::Success` if events are read,
     /// `Status::Failure` if not, or an error.
     fn update (&self, timeout: f64) -> Status {
-        unsafe { Status::from(pffi::puglUpdate(self.world(), timeout)) }
+        let view = self.view();
+        let status = with_backend(view, |backend| backend.update(timeout));
+        let dispatch = with_instance(view, |instance| instance.dispatch);
+        while let Some(event) = with_instance(view, |instance| instance.backend.next_event()) {
+            dispatch(view, event);
+        }
+        dispatch(view, ViewEvent::UpdateDone);
+        status
     }
 
     /// Activate a repeating timer event.
@@ -400,7 +553,7 @@ pub trait PuglViewTrait {
     /// `Status::Success` or `Status::Failure` if timers are not
     /// supported on the system
     fn start_timer(&self, id: usize, timeout: f64) -> Status {
-        unsafe { Status::from(pffi::puglStartTimer(self.view(), id, timeout)) }
+        with_instance(self.view(), |instance| instance.backend.start_timer(id, timeout))
     }
 
     /// Stop an active timer
@@ -411,24 +564,403 @@ pub trait PuglViewTrait {
     /// ## Returns
     /// `Status::Success` or `Status::Failure` if no such timer was found.
     fn stop_timer(&self, id: usize) -> Status {
-        unsafe { Status::from(pffi::puglStopTimer(self.view(), id)) }
+        with_instance(self.view(), |instance| instance.backend.stop_timer(id))
+    }
+
+    /// Returns the keyboard modifiers currently held
+    ///
+    /// Unlike the modifiers reported with an event, this can be
+    /// queried at any time, e.g. in a [`timer_event()`](#method.timer_event).
+    fn modifiers(&self) -> Modifiers {
+        with_instance(self.view(), |instance| instance.state.input.modifiers())
+    }
+
+    /// Returns `true` if a key is currently pressed that was pressed as `key`
+    ///
+    /// Keys are tracked by their key code, so a key counts as released
+    /// by its release even if that reports a different value, e.g. as
+    /// a modifier has been pressed in between. All keys are considered
+    /// released when the view loses the focus.
+    fn is_key_pressed(&self, key: KeyVal) -> bool {
+        with_instance(self.view(), |instance| instance.state.input.is_key_pressed(key))
+    }
+
+    /// Returns `true` if the physical key `key` is currently pressed
+    ///
+    /// Unlike [`is_key_pressed()`](#method.is_key_pressed) this does
+    /// not depend on the keyboard layout or the modifiers. All keys are
+    /// considered released when the view loses the focus.
+    fn is_physical_key_pressed(&self, key: PhysicalKey) -> bool {
+        with_instance(self.view(), |instance| instance.state.input.is_physical_key_pressed(key))
+    }
+
+    /// Returns the mouse buttons currently pressed
+    ///
+    /// All buttons are considered released when the view loses the focus.
+    fn mouse_buttons(&self) -> MouseButtons {
+        with_instance(self.view(), |instance| instance.state.input.buttons())
+    }
+
+    /// Add a keyboard shortcut
+    ///
+    /// When the keys of `shortcut` are pressed,
+    /// [`shortcut_event()`](#method.shortcut_event) is called with
+    /// `id` instead of [`event()`](#method.event). Key repeats of the
+    /// shortcut are dropped. A shortcut already added before is
+    /// rebound to `id`.
+    ///
+    /// ## Parameters
+    /// * `shortcut` – The shortcut, e.g. parsed from "Ctrl+Shift+Z"
+    /// * `id` – The ID that is passed to [`shortcut_event()`](#method.shortcut_event)
+    fn add_shortcut(&self, shortcut: Shortcut, id: usize) -> Status {
+        with_instance(self.view(), |instance| instance.state.shortcuts.insert(shortcut, id));
+        Status::Success
+    }
+
+    /// Remove a keyboard shortcut
+    ///
+    /// ## Parameters
+    /// * `shortcut` – The shortcut previously passed to [`add_shortcut()`](#method.add_shortcut)
+    ///
+    /// ## Returns
+    /// `Status::Success` or `Status::Failure` if no such shortcut was found.
+    fn remove_shortcut(&self, shortcut: &Shortcut) -> Status {
+        match with_instance(self.view(), |instance| instance.state.shortcuts.remove(shortcut)) {
+            Some(_) => Status::Success,
+            None => Status::Failure
+        }
     }
 }
 
+
 /// A struct for a pugl UI object
 /// `T` is struct implementing the [`PuglViewTrait`](trait.PuglViewTrait.html),
 /// representing the UI's state
-pub struct PuglView<T: PuglViewTrait> {
-    ui_type: std::marker::PhantomData<T>,
+///
+/// `B` is the backend the view is running on. By default it is
+/// [`PuglBackend`](struct.PuglBackend.html), which opens a real window.
+/// Tests can use [`MockBackend`](struct.MockBackend.html) instead,
+/// which needs no window system and lets the test fake the events.
+pub struct PuglView<T: PuglViewTrait, B: Backend = PuglBackend> {
+    ui_type: std::marker::PhantomData<(T, B)>,
     instance: PuglViewFFI
 }
 
+/// The instance of a view a [`PuglViewFFI`](type.PuglViewFFI.html) points to
+///
+/// It connects the UI object with the backend driving the view.
+pub struct ViewInstance {
+    pub(crate) backend: Box<dyn BackendImpl>,
+    pub(crate) state: ViewState,
+    ui: *mut std::ffi::c_void,
+    dispatch: fn(PuglViewFFI, ViewEvent) -> Status
+}
+
+/// State of a view that the backend does not keep track of
+#[derive(Default)]
+pub(crate) struct ViewState {
+    pub(crate) window_icon: Vec<WindowIcon>,
+    /// The state requested by `set_window_state()`, applied on realization
+    pub(crate) requested_window_state: WindowState,
+    /// The state last reported by `window_state_changed()`
+    pub(crate) observed_window_state: WindowState,
+    pub(crate) input: InputState,
+    pub(crate) shortcuts: ShortcutMap<usize>,
+    #[cfg(feature = "serde")]
+    pub(crate) recorder: Option<Recorder>
+}
+
+/// Calls `f` with the instance of `view`
+///
+/// The instance is only borrowed while `f` runs, so `f` must neither
+/// call into the UI object nor into the backend in a way that
+/// dispatches events, as both access the instance again.
+fn with_instance<R>(view: PuglViewFFI, f: impl FnOnce(&mut ViewInstance) -> R) -> R {
+    unsafe { f(&mut *view) }
+}
+
+/// Makes the call of the backend of `view` returned by `f`
+///
+/// For the calls of the backend that may dispatch events to the view,
+/// which are made after the instance is no longer borrowed.
+fn with_backend(view: PuglViewFFI, f: impl FnOnce(&mut dyn BackendImpl) -> DeferredCall) -> Status {
+    let call = with_instance(view, |instance| f(&mut *instance.backend));
+    call()
+}
+
+/// Calls `f` with the UI object of type `T` of `view`
+///
+/// The instance is not borrowed while `f` runs, so the UI can call
+/// the methods of the view.
+fn with_ui<T: PuglViewTrait, R>(view: PuglViewFFI, f: impl FnOnce(&mut T) -> R) -> R {
+    let ui = with_instance(view, |instance| instance.ui as *mut T);
+    unsafe { f(&mut *ui) }
+}
+
+fn set_relative_pointer_mode(view: PuglViewFFI, enable: bool) -> Status {
+    with_instance(view, |instance| {
+        if instance.state.input.relative_anchor().is_some() == enable {
+            return Status::Success;
+        }
+        if !instance.backend.is_realized() {
+            return Status::Failure;
+        }
+        match instance.state.input.relative_anchor() {
+            None => {
+                let status = instance.backend.grab_pointer();
+                if status != Status::Success {
+                    return status;
+                }
+            }
+            Some(anchor) => {
+                instance.backend.set_pointer_position(anchor);
+                instance.backend.ungrab_pointer();
+            }
+        }
+        instance.state.input.set_relative_mode(enable);
+        instance.backend.set_cursor_visible(!enable)
+    })
+}
+
+fn view_hint(view: PuglViewFFI, hint: p::PuglViewHint) -> p::PuglViewHintValue {
+    with_instance(view, |instance| instance.backend.view_hint(hint))
+}
+
+fn set_view_hint(view: PuglViewFFI, hint: p::PuglViewHint, value: p::PuglViewHintValue) -> Status {
+    with_instance(view, |instance| instance.backend.set_view_hint(hint, value))
+}
+
+/// Dispatches an event of the backend to the UI object of type `T`
+pub(crate) fn dispatch<T: PuglViewTrait>(view: PuglViewFFI, event: ViewEvent) -> Status {
+    #[cfg(feature = "serde")]
+    record(view, &event);
+    match event {
+        ViewEvent::Input(event) => dispatch_input::<T>(view, event),
+        ViewEvent::FocusIn => {
+            let status = with_ui(view, T::focus_in);
+            update_window_state::<T>(view);
+            status
+        }
+        ViewEvent::FocusOut => {
+            set_relative_pointer_mode(view, false);
+            let end = with_instance(view, |instance| instance.state.input.end_scroll_gesture(instance.backend.time()));
+            if let Some(end) = end {
+                with_ui(view, |ui: &mut T| ui.event(end));
+            }
+            with_instance(view, |instance| instance.state.input.reset());
+            let status = with_ui(view, T::focus_out);
+            update_window_state::<T>(view);
+            status
+        }
+        ViewEvent::Timer(id) => with_ui(view, |ui: &mut T| ui.timer_event(id)),
+        ViewEvent::Close => {
+            with_ui(view, T::close_request);
+            Status::Success
+        }
+        ViewEvent::Expose(expose, cr) => {
+            with_ui(view, |ui: &mut T| ui.exposed(&expose, &cr));
+            Status::Success
+        }
+        ViewEvent::Configure(size) => {
+            with_ui(view, |ui: &mut T| ui.resize(size));
+            update_window_state::<T>(view);
+            Status::Success
+        }
+        ViewEvent::WindowStateChanged => {
+            update_window_state::<T>(view);
+            Status::Success
+        }
+        ViewEvent::UpdateDone => {
+            end_scroll_gesture::<T>(view, None);
+            Status::Success
+        }
+    }
+}
+
+/// Dispatches the end of the running scroll gesture if it is over before `next`
+fn end_scroll_gesture<T: PuglViewTrait>(view: PuglViewFFI, next: Option<&Event>) {
+    let end = with_instance(view, |instance| {
+        let input = &mut instance.state.input;
+        let time = instance.backend.time();
+        match input.is_scroll_gesture_over(next, time) {
+            true => input.end_scroll_gesture(next.map_or(time, |ev| ev.context.time)),
+            false => None
+        }
+    });
+    if let Some(end) = end {
+        with_ui(view, |ui: &mut T| ui.event(end));
+    }
+}
+
+/// Writes `event` to the recording of the view, if it is being recorded
+#[cfg(feature = "serde")]
+fn record(view: PuglViewFFI, event: &ViewEvent) {
+    with_instance(view, |instance| {
+        let recorder = match instance.state.recorder.as_mut() {
+            Some(recorder) => recorder,
+            None => return
+        };
+        if let Some(event) = RecordedEvent::from_view_event(event) {
+            recorder.record(&Record { time: instance.backend.time(), event });
+        }
+    })
+}
+
+fn dispatch_input<T: PuglViewTrait>(view: PuglViewFFI, event: Event) -> Status {
+    end_scroll_gesture::<T>(view, Some(&event));
+    let event = with_instance(view, |instance| {
+        let event = instance.state.input.process(event)?;
+        if let (EventType::RelativeMove(_), Some(anchor)) = (event.data, instance.state.input.relative_anchor()) {
+            instance.backend.set_pointer_position(anchor);
+        }
+        Some(event)
+    });
+    let event = match event {
+        Some(event) => event,
+        None => return Status::Success
+    };
+    if let Some(key) = event.try_keypress() {
+        if key.is_repeat && with_ui(view, |ui: &mut T| ui.is_ignoring_key_repeats()) == ViewHintBool::True {
+            return Status::Success
+        }
+        if let Some(id) = with_instance(view, |instance| instance.state.shortcuts.lookup(&key).copied()) {
+            return match key.is_repeat {
+                true => Status::Success,
+                false => with_ui(view, |ui: &mut T| ui.shortcut_event(id))
+            }
+        }
+    }
+    with_ui(view, |ui: &mut T| ui.event(event))
+}
+
+/// Calls `window_state_changed()` if the window state differs from the last known one
+pub(crate) fn update_window_state<T: PuglViewTrait>(view: PuglViewFFI) {
+    let window_state = with_ui(view, |ui: &mut T| ui.window_state());
+    let changed = with_instance(view, |instance| {
+        let changed = window_state != instance.state.observed_window_state;
+        instance.state.observed_window_state = window_state;
+        changed
+    });
+    if changed {
+        with_ui(view, |ui: &mut T| ui.window_state_changed(window_state));
+    }
+}
+
+impl<T: PuglViewTrait, B: Backend> PuglView<T, B> {
+    /// Sets up a new `PuglView` for a heap allocated object of `T` implementing
+    /// [`PuglViewTrait`](trait.PuglViewTrait.html).
+    ///
+    /// Can be called with a closure taking a [`PuglViewFFI`](type.PuglViewFFI.html)
+    /// returning an [`PuglViewTrait`](trait.PuglViewTrait) object.
+    ///
+    /// The trait object should retain the `PuglViewFFI` pointer to implement
+    /// [`PuglViewTrait:view()`](trait.PuglViewTrait.html#tymethod.view).
+    pub fn new<F>(parent_window: *mut std::ffi::c_void, new: F) -> Box<Self>
+    where F: FnOnce(PuglViewFFI) -> T {
+        let view = Box::new(PuglView::<T, B> {
+            ui_type: PhantomData,
+            instance: Box::into_raw(Box::new(ViewInstance {
+                backend: Box::new(B::new::<T>(parent_window)),
+                state: ViewState::default(),
+                ui: std::ptr::null_mut(),
+                dispatch: dispatch::<T>
+            }))
+        });
+
+        let ui = Box::new(new(view.instance));
+        with_instance(view.instance, |instance| {
+            instance.ui = Box::into_raw(ui) as *mut std::ffi::c_void;
+            instance.backend.set_handle(view.instance);
+        });
+        view
+    }
+
+    /// Returns a handle to the object `T`
+    pub fn handle(&mut self) -> &mut T {
+        let ui = with_instance(self.instance, |instance| instance.ui as *mut T);
+        unsafe { &mut *ui }
+    }
+
+    /// Returns a handle to the view
+    pub fn view(&self) -> PuglViewFFI {
+        self.instance
+    }
+
+    /// Retuns a handle to the native window
+    pub fn native_window(&self) -> p::PuglNativeView {
+        with_instance(self.instance, |instance| instance.backend.native_window())
+    }
+
+    /// Starts recording the events of the view to `writer`
+    ///
+    /// All input events, resizes, focus changes, close requests and
+    /// timer events are written as [`Record`](struct.Record.html)s
+    /// serialized as JSON, one per line, until
+    /// [`stop_recording()`](#method.stop_recording) is called.
+    /// Exposes are not recorded. A running recording is stopped
+    /// first, its errors are discarded.
+    ///
+    /// The recording can be read by [`read_recording()`](fn.read_recording.html)
+    /// and replayed by [`replay()`](#method.replay).
+    ///
+    /// Only available with the feature `serde`.
+    #[cfg(feature = "serde")]
+    pub fn start_recording<W: io::Write + 'static>(&mut self, writer: W) {
+        let recorder = Recorder::new(Box::new(writer));
+        with_instance(self.instance, |instance| instance.state.recorder = Some(recorder));
+    }
+
+    /// Stops recording and flushes the recording
+    ///
+    /// Returns the first error that occurred while writing the recording.
+    ///
+    /// Only available with the feature `serde`.
+    #[cfg(feature = "serde")]
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        match with_instance(self.instance, |instance| instance.state.recorder.take()) {
+            Some(recorder) => recorder.finish(),
+            None => Ok(())
+        }
+    }
+
+    /// Dispatches `record` to the UI as if it came from the window system now
+    ///
+    /// For a recorded resize the frame of the view is resized as
+    /// well. To reproduce a session with a live view, the records
+    /// need to be replayed in the main loop at the recorded pace.
+    pub fn replay(&mut self, record: &Record) -> Status {
+        if let RecordedEvent::Configure(size) = record.event {
+            with_instance(self.instance, |instance| {
+                let frame = Rect { pos: instance.backend.frame().pos, size };
+                instance.backend.set_frame(frame)
+            });
+        }
+        dispatch::<T>(self.instance, record.event.to_view_event())
+    }
+}
+
+impl<T: PuglViewTrait, B: Backend> Drop for PuglView<T, B> {
+    fn drop(&mut self) {
+        unsafe {
+            let instance = Box::from_raw(self.instance);
+            let ui = instance.ui as *mut T;
+            drop(instance);
+            drop(Box::from_raw(ui));
+        };
+    }
+}
+
+/// The backend running a view on the window system by pugl
+pub struct PuglBackend {
+    world: *mut p::PuglWorld,
+    view: *mut p::PuglView,
+    window_state_watcher: Option<native::WindowStateWatcher>
+}
 
 unsafe extern "C"
-fn event_handler<T: PuglViewTrait>(view_ptr: PuglViewFFI, event_ptr: *const p::PuglEvent) -> p::PuglStatus {
+fn event_handler<T: PuglViewTrait>(view_ptr: *mut p::PuglView, event_ptr: *const p::PuglEvent) -> p::PuglStatus {
     let ev = *event_ptr;
-    let handle: &mut T = &mut *(pffi::puglGetHandle(view_ptr) as *mut T);
-    //eprintln!("event_handler: {:?}", ev.type_);
+    let view = pffi::puglGetHandle(view_ptr) as PuglViewFFI;
     let event = match ev.type_ {
         p::PuglEventType_PUGL_KEY_PRESS => {
             Event { data: EventType::KeyPress(Key::from(ev.key)), context: EventContext::from(ev.key) }
@@ -446,41 +978,59 @@ fn event_handler<T: PuglViewTrait>(view_ptr: PuglViewFFI, event_ptr: *const p::P
             Event { data: EventType::MouseMove(MotionContext::from(ev.motion)), context: EventContext::from(ev.motion) }
         },
         p::PuglEventType_PUGL_POINTER_IN => {
-            Event { data: EventType::PointerIn, context: EventContext::from(ev.crossing) }
+            Event { data: EventType::PointerIn(CrossingMode::from(ev.crossing.mode)), context: EventContext::from(ev.crossing) }
         }
         p::PuglEventType_PUGL_POINTER_OUT => {
-            Event { data: EventType::PointerOut, context: EventContext::from(ev.crossing) }
+            Event { data: EventType::PointerOut(CrossingMode::from(ev.crossing.mode)), context: EventContext::from(ev.crossing) }
         }
         p::PuglEventType_PUGL_SCROLL => {
             Event { data: EventType::Scroll(Scroll::from(ev.scroll)), context: EventContext::from(ev.scroll) }
         },
         p::PuglEventType_PUGL_FOCUS_IN => {
-            return handle.focus_in() as p::PuglStatus
+            return dispatch::<T>(view, ViewEvent::FocusIn) as p::PuglStatus
         },
         p::PuglEventType_PUGL_FOCUS_OUT => {
-            return handle.focus_out() as p::PuglStatus
+            return dispatch::<T>(view, ViewEvent::FocusOut) as p::PuglStatus
         },
         p::PuglEventType_PUGL_TIMER => {
-            return handle.timer_event(ev.timer.id) as p::PuglStatus
+            return dispatch::<T>(view, ViewEvent::Timer(ev.timer.id)) as p::PuglStatus
         }
         p::PuglEventType_PUGL_CLOSE => {
-            handle.close_request ();
-            return p::PuglStatus_PUGL_SUCCESS
+            return dispatch::<T>(view, ViewEvent::Close) as p::PuglStatus
         }
         p::PuglEventType_PUGL_EXPOSE => {
-            let cr = cairo::Context::from_raw_borrow (pffi::puglGetContext(view_ptr) as *mut cairo_sys::cairo_t);
-            handle.exposed (&ExposeArea::from(ev.expose), &cr);
-            return p::PuglStatus_PUGL_SUCCESS
+            let cr = cairo::Context::from_raw_none(pffi::puglGetContext(view_ptr) as *mut cairo_sys::cairo_t);
+            return dispatch::<T>(view, ViewEvent::Expose(ExposeArea::from(ev.expose), cr)) as p::PuglStatus
         },
         p::PuglEventType_PUGL_CONFIGURE => {
-            let size = Size::from(ev.configure);
-            handle.resize (size);
+            return dispatch::<T>(view, ViewEvent::Configure(Size::from(ev.configure))) as p::PuglStatus
+        },
+        p::PuglEventType_PUGL_CREATE => {
+            with_instance(view, |instance| {
+                let state = &mut instance.state;
+                let display = pffi::puglGetNativeWorld(pffi::puglGetWorld(view_ptr));
+                let window = pffi::puglGetNativeWindow(view_ptr);
+                if !state.window_icon.is_empty() {
+                    native::set_window_icon(display, window, &state.window_icon);
+                }
+                if !state.requested_window_state.is_empty() {
+                    native::set_window_state(display, window, state.requested_window_state, true);
+                }
+                native::set_detectable_auto_repeat(display);
+                let (click_time, click_distance) = native::double_click_settings(display);
+                if let Some(time) = click_time {
+                    state.input.clicks.time = time;
+                }
+                if let Some(distance) = click_distance {
+                    state.input.clicks.distance = distance;
+                }
+            });
             return p::PuglStatus_PUGL_SUCCESS
         },
         _  => { return p::PuglStatus_PUGL_SUCCESS }
 
     };
-    handle.event (event) as p::PuglStatus
+    dispatch::<T>(view, ViewEvent::Input(event)) as p::PuglStatus
 }
 
 #[cfg(test)]
@@ -492,66 +1042,202 @@ unsafe fn get_backend() -> *const p::PuglBackend {
     pffi::puglCairoBackend()
 }
 
-impl<T: PuglViewTrait> PuglView<T> {
-    /// Sets up a new `PuglView` for a heap allocated object of `T` implementing
-    /// [`PuglViewTrait`](trait.PuglViewTrait.html).
-    ///
-    /// Can be called with a closure taking a [`PuglViewFFI`](type.PuglViewFFI.html)
-    /// returning an [`PuglViewTrait`](trait.PuglViewTrait) object.
-    ///
-    /// The trait object should retain the `PuglViewFFI` pointer to implement
-    /// [`PuglViewTrait:view()`](trait.PuglViewTrait.html#tymethod.view).
-    pub fn new<F>(parent_window: *mut std::ffi::c_void, new: F) -> Box<Self>
-    where F: FnOnce(PuglViewFFI) -> T {
-        let view = Box::new(PuglView::<T> {
-            ui_type: PhantomData,
-            instance: unsafe {
-                pffi::puglNewView(pffi::puglNewWorld(p::PuglWorldType_PUGL_PROGRAM, 0))
-            }
-        });
+impl PuglBackend {
+    fn display(&self) -> *mut std::ffi::c_void {
+        unsafe { pffi::puglGetNativeWorld(self.world) }
+    }
+}
 
-        let ui = Box::new(new(view.instance));
+impl Backend for PuglBackend {}
+
+impl BackendImpl for PuglBackend {
+    fn new<T: PuglViewTrait>(parent_window: *mut std::ffi::c_void) -> PuglBackend {
         unsafe {
+            let world = pffi::puglNewWorld(p::PuglWorldType_PUGL_PROGRAM, 0);
+            let view = pffi::puglNewView(world);
             if !parent_window.is_null() {
-                pffi::puglSetParentWindow(view.instance, parent_window as usize);
+                pffi::puglSetParentWindow(view, parent_window as usize);
             }
-            pffi::puglSetHandle(view.instance, Box::into_raw(ui) as p::PuglHandle);
-            pffi::puglSetEventFunc(view.instance, Some(event_handler::<T>));
-            pffi::puglSetBackend(view.instance, get_backend());
-            pffi::puglSetViewHint(view.instance, p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT, true as i32);
+            pffi::puglSetEventFunc(view, Some(event_handler::<T>));
+            pffi::puglSetBackend(view, get_backend());
+            pffi::puglSetViewHint(view, p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT, true as i32);
+            PuglBackend { world, view, window_state_watcher: None }
         }
-        view
     }
 
-    /// Returns a handle to the object `T`
-    pub fn handle(&mut self) -> &mut T {
+    fn set_handle(&mut self, view: PuglViewFFI) {
+        unsafe { pffi::puglSetHandle(self.view, view as p::PuglHandle) }
+    }
+
+    fn world(&self) -> *mut p::PuglWorld {
+        self.world
+    }
+
+    fn native_window(&self) -> p::PuglNativeView {
+        unsafe { pffi::puglGetNativeWindow(self.view) }
+    }
+
+    fn is_realized(&self) -> bool {
+        self.native_window() != 0
+    }
+
+    fn post_redisplay(&mut self) -> Status {
+        unsafe { Status::from(pffi::puglPostRedisplay(self.view)) }
+    }
+
+    fn post_redisplay_rect(&mut self, rect: Rect) -> Status {
+        unsafe { Status::from(pffi::puglPostRedisplayRect(self.view, rect.into())) }
+    }
+
+    fn frame(&self) -> Rect {
+        unsafe { pffi::puglGetFrame(self.view).into() }
+    }
+
+    fn set_frame(&mut self, frame: Rect) -> Status {
+        unsafe { Status::from(pffi::puglSetFrame(self.view, frame.into())) }
+    }
+
+    fn monitors(&self) -> Vec<Monitor> {
+        unsafe { native::monitors(self.display()) }
+    }
+
+    fn set_default_size(&mut self, width: i32, height: i32) -> Status {
+        unsafe { Status::from(pffi::puglSetDefaultSize(self.view, width, height)) }
+    }
+
+    fn set_min_size(&mut self, width: i32, height: i32) -> Status {
+        unsafe { Status::from(pffi::puglSetMinSize(self.view, width, height)) }
+    }
+
+    fn set_max_size(&mut self, width: i32, height: i32) -> Status {
+        unsafe { Status::from(pffi::puglSetMaxSize(self.view, width, height)) }
+    }
+
+    fn set_aspect_ratio(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Status {
+        unsafe { Status::from(pffi::puglSetAspectRatio(self.view, min_x, min_y, max_x, max_y)) }
+    }
+
+    fn view_hint(&self, hint: p::PuglViewHint) -> p::PuglViewHintValue {
+        unsafe { pffi::puglGetViewHint(self.view, hint) }
+    }
+
+    fn set_view_hint(&mut self, hint: p::PuglViewHint, value: p::PuglViewHintValue) -> Status {
+        unsafe { Status::from(pffi::puglSetViewHint(self.view, hint, value)) }
+    }
+
+    fn set_window_title(&mut self, title: &str) -> Status {
+        let title =
+            std::ffi::CString::new(title.as_bytes())
+                .expect("window title must not contain 0 bytes");
         unsafe {
-            &mut *(pffi::puglGetHandle(self.instance) as *mut T) as &mut T
+            Status::from(pffi::puglSetWindowTitle(self.view, title.into_raw()))
         }
     }
 
-    /// Returns a handle to the window system's view
-    pub fn view(&self) -> PuglViewFFI {
-        self.instance
+    fn set_window_icon(&mut self, icons: &[WindowIcon]) -> Status {
+        match self.native_window() {
+            0 => Status::Success,
+            window => unsafe { native::set_window_icon(self.display(), window, icons) }
+        }
     }
 
-    /// Retuns a handle to the native window
-    pub fn native_window(&self) -> p::PuglNativeView {
-        unsafe { pffi::puglGetNativeWindow(self.view()) }
+    fn set_window_state(&mut self, state: WindowState, enable: bool) -> Status {
+        match self.native_window() {
+            0 => Status::Success,
+            window => unsafe { native::set_window_state(self.display(), window, state, enable) }
+        }
+    }
+
+    fn window_state(&self) -> Option<WindowState> {
+        match self.native_window() {
+            0 => None,
+            window => Some(unsafe { native::window_state(self.display(), window) })
+        }
+    }
+
+    fn realize(&mut self) -> DeferredCall {
+        let view = self.view;
+        Box::new(move || unsafe { Status::from(pffi::puglRealize(view)) })
+    }
+
+    fn show_window(&mut self) -> DeferredCall {
+        let view = self.view;
+        Box::new(move || unsafe { Status::from(pffi::puglShow(view)) })
+    }
+
+    fn hide_window(&mut self) -> Status {
+        unsafe { Status::from(pffi::puglHideWindow(self.view)) }
+    }
+
+    fn is_visible(&self) -> bool {
+        unsafe { pffi::puglGetVisible(self.view) }
+    }
+
+    fn request_attention(&mut self) -> Status {
+        unsafe { Status::from(pffi::puglRequestAttention(self.view)) }
+    }
+
+    fn grab_pointer(&mut self) -> Status {
+        unsafe { native::grab_pointer(self.display(), self.native_window()) }
+    }
+
+    fn ungrab_pointer(&mut self) -> Status {
+        unsafe { native::ungrab_pointer(self.display()) }
+    }
+
+    fn set_pointer_position(&mut self, pos: Coord) -> Status {
+        unsafe { native::warp_pointer(self.display(), self.native_window(), pos) }
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Status {
+        unsafe { native::set_cursor_visible(self.display(), self.native_window(), visible) }
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) -> Status {
+        unsafe { Status::from(pffi::puglSetCursor(self.view, cursor.into())) }
+    }
+
+    fn time(&self) -> f64 {
+        unsafe { pffi::puglGetTime(self.world) }
+    }
+
+    fn update(&mut self, timeout: f64) -> DeferredCall {
+        let world = self.world;
+        Box::new(move || unsafe { Status::from(pffi::puglUpdate(world, timeout)) })
+    }
+
+    fn next_event(&mut self) -> Option<ViewEvent> {
+        if self.window_state_watcher.is_none() {
+            // the window only exists once the view is realized
+            self.window_state_watcher = unsafe { native::WindowStateWatcher::new(self.display(), self.native_window()) };
+        }
+        let changed = self.window_state_watcher.as_mut().map_or(false, |watcher| watcher.take_change());
+        if changed { Some(ViewEvent::WindowStateChanged) } else { None }
+    }
+
+    fn start_timer(&mut self, id: usize, timeout: f64) -> Status {
+        unsafe { Status::from(pffi::puglStartTimer(self.view, id, timeout)) }
+    }
+
+    fn stop_timer(&mut self, id: usize) -> Status {
+        unsafe { Status::from(pffi::puglStopTimer(self.view, id)) }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
     }
 }
 
-impl<T: PuglViewTrait> Drop for PuglView<T> {
+impl Drop for PuglBackend {
     fn drop(&mut self) {
         unsafe {
-            let instance = self.instance as PuglViewFFI;
-            let world = pffi::puglGetWorld(instance);
-            pffi::puglFreeView(instance);
-            pffi::puglFreeWorld(world);
-        };
+            pffi::puglFreeView(self.view);
+            pffi::puglFreeWorld(self.world);
+        }
     }
 }
 
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -650,6 +1336,16 @@ mod test {
             .returning(|_| std::ptr::null_mut());
         expectations.push(ctx_get_world);
 
+        let ctx_get_native_world = Box::new(pffi::puglGetNativeWorld_context());
+        ctx_get_native_world.expect()
+            .returning(|_| std::ptr::null_mut());
+        expectations.push(ctx_get_native_world);
+
+        let ctx_get_native_window = Box::new(pffi::puglGetNativeWindow_context());
+        ctx_get_native_window.expect()
+            .return_const(0 as p::PuglNativeView);
+        expectations.push(ctx_get_native_window);
+
         let ctx_free_view = Box::new(pffi::puglFreeView_context());
         ctx_free_view.expect()
             .times(1)
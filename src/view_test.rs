@@ -1,247 +1,328 @@
-use std::collections::VecDeque;
-use std::marker::PhantomData;
+use std::collections::{HashMap, VecDeque};
 
 use crate::types::*;
 use crate::pugl as p;
-
-pub type PuglViewFFI = *mut PuglViewMock;
-
-pub struct PuglView<T: PuglViewTrait> {
-    ui_type: std::marker::PhantomData<T>,
-    instance: PuglViewFFI
-}
-
-impl<T: PuglViewTrait> PuglView<T> {
-    pub fn new<F>(_parent_window: *mut std::ffi::c_void, new: F) -> Box<Self>
-    where F: FnOnce(PuglViewFFI) -> T {
-
-        let view = Box::new(PuglView::<T> {
-            instance: Box::into_raw(Box::new(PuglViewMock::default())),
-            ui_type: PhantomData
-        });
-
-        let ui = Box::new(new(view.instance));
-
-        unsafe {
-            let ui_ptr = Box::leak(ui) as *mut T;
-            (*view.instance).ui_ptr = ui_ptr as *mut std::ffi::c_void;
-        }
-
-        view
+use crate::shortcut::Shortcut;
+use crate::snapshot;
+use crate::record::*;
+use crate::backend::{Backend, BackendImpl, DeferredCall, ViewEvent};
+use crate::view::*;
+
+impl<T: PuglViewTrait> PuglView<T, MockBackend> {
+    /// Returns the mock backend of the view to inspect what the UI has requested
+    pub fn mock_instance(&self) -> &MockBackend {
+        mock(self.view())
     }
 
-    pub fn handle(&mut self) -> &mut T {
-        unsafe {
-            &mut *((*self.instance).ui_ptr as *mut T) as &mut T
-        }
+    /// Resizes the view to `size` as if the window manager did so
+    ///
+    /// The UI is notified by `resize()`.
+    pub fn fake_resize(&mut self, size: Size) {
+        mock(self.view()).frame.size = size;
+        dispatch::<T>(self.view(), ViewEvent::Configure(size));
     }
 
-    pub fn mock_instance(&self) -> &PuglViewMock {
-        unsafe {
-            &*self.instance
-        }
+    /// Puts the window into `state` as if the window manager did so
+    ///
+    /// The UI is notified by `window_state_changed()` if the state differs
+    /// from the last one reported.
+    pub fn fake_window_state(&mut self, state: WindowState) {
+        mock(self.view()).window_state = state;
+        dispatch::<T>(self.view(), ViewEvent::WindowStateChanged);
     }
 
-    pub fn fake_resize(&mut self, size: Size) {
-	unsafe {
-	    (*self.instance).frame.size = size;
-	}
-
-	self.handle().resize(size)
+    /// Sets the monitors returned by `monitors()`
+    pub fn fake_monitors(&mut self, monitors: Vec<Monitor>) {
+        mock(self.view()).monitors = monitors;
     }
 
+    /// Gives the focus to the view
+    ///
+    /// Like a window manager, this ends a pending request for attention,
+    /// see [`MockBackend::attention_requested()`](struct.MockBackend.html#method.attention_requested).
     pub fn fake_focus_in(&mut self) {
-        self.handle().focus_in();
+        mock(self.view()).attention_requested = false;
+        dispatch::<T>(self.view(), ViewEvent::FocusIn);
     }
 
+    /// Takes the focus away from the view
+    ///
+    /// All keys and buttons are considered released afterwards and the
+    /// relative pointer mode is left.
     pub fn fake_focus_out(&mut self) {
-        self.handle().focus_out();
+        dispatch::<T>(self.view(), ViewEvent::FocusOut);
     }
 
+    /// Queues `ev` to be dispatched by the next call of `update()`
+    ///
+    /// The event is stamped with the current time of the virtual clock,
+    /// see [`advance_time()`](#method.advance_time).
     pub fn queue_event(&mut self, ev: Event) {
-        unsafe {
-            (*self.instance).queue_event(ev);
-        }
-    }
-}
-
-
-
-pub trait PuglViewTrait {
-
-    fn event(&mut self, ev: Event) -> Status;
-
-    fn exposed (&mut self, _expose: &ExposeArea, _cr: &cairo::Context) {}
-
-    fn resize (&mut self, _size: Size) {}
-
-    fn close_request(&mut self) {}
-
-    fn focus_in(&mut self) -> Status { Status::Success }
-
-    fn focus_out(&mut self) -> Status { Status::Success }
-
-    fn timer_event(&mut self, _id: usize) -> Status { Status::Success }
-
-    fn view (&self) -> PuglViewFFI;
-
-    fn world (&self) -> *mut p::PuglWorld {
-        std::ptr::null_mut::<p::PuglWorld>()
-    }
-
-    fn post_redisplay (&self) -> Status {
-        Status::Success
+        mock(self.view()).queue_event(ev);
     }
 
-    fn post_redisplay_rect(&self, pos: Coord, size: Size) -> Status {
-        let _p_rect = p::PuglRect {
-            x: pos.x,
-            y: pos.y,
-            width: size.w,
-            height: size.h
-        };
-        Status::Success
-    }
-
-    fn get_frame(&self) -> Rect {
-        unsafe { (*self.view()).frame }
-    }
-
-    fn set_frame (&self, frame: Rect) -> Status {
-        unsafe {
-            (*self.view()).frame = frame;
+    /// Makes the UI draw `area` of the view by calling `exposed()`
+    ///
+    /// The UI draws on the surface returned by
+    /// [`MockBackend::surface()`](struct.MockBackend.html#method.surface).
+    /// If the view has not been drawn before or the view has been
+    /// resized, the whole view is exposed. Nothing is exposed if the
+    /// view has no size.
+    pub fn fake_expose(&mut self, area: ExposeArea) {
+        let area = Rect { pos: area.pos, size: area.size };
+        if let Some(event) = mock(self.view()).expose(area) {
+            dispatch::<T>(self.view(), event);
         }
-        Status::Success
     }
 
-    fn set_default_size(&self, width: i32, height: i32) -> Status {
-        unsafe {
-            (*self.view()).default_width = width;
-            (*self.view()).default_height = height;
-        }
-        Status::Success
+    /// Renders the view at `size` and returns the image
+    ///
+    /// The view is resized to `size` and exposed entirely on a new
+    /// surface with `scale_factor` times as many pixels in each
+    /// direction as `size`. Returns `None` if `size` is empty.
+    pub fn render(&mut self, size: Size, scale_factor: f64) -> Option<&cairo::ImageSurface> {
+        let mock = mock(self.view());
+        mock.scale_factor = scale_factor;
+        mock.surface = None;
+        self.fake_resize(size);
+        self.fake_expose(ExposeArea { pos: Coord::default(), size });
+        self.mock_instance().surface()
     }
 
-    fn set_min_size (&self, width: i32, height: i32) -> Status {
-        unsafe {
-            (*self.view()).min_width = width;
-            (*self.view()).min_height = height;
+    /// Renders the view and compares the image with the snapshot stored as PNG at `path`
+    ///
+    /// The view is rendered like by [`render()`](#method.render). The
+    /// test fails if the sizes of the images differ or if any color
+    /// channel of a pixel differs by more than `tolerance`. Then the
+    /// rendered image and an image with the mismatching pixels in red
+    /// are saved next to the snapshot, with the extensions
+    /// `actual.png` and `diff.png`.
+    ///
+    /// If the environment variable `PUGL_BLESS_SNAPSHOTS` is set, the
+    /// rendered image is stored as the snapshot instead. This is how
+    /// new snapshots are created and outdated ones updated.
+    pub fn assert_snapshot<P: AsRef<std::path::Path>>(&mut self, path: P, size: Size, scale_factor: f64, tolerance: u8) {
+        let bless = std::env::var_os(snapshot::BLESS_VAR).is_some();
+        let surface = self.render(size, scale_factor).expect("cannot render a view of empty size");
+        if let Err(msg) = snapshot::check(surface, path.as_ref(), tolerance, bless) {
+            panic!("{}", msg);
         }
-        Status::Success
     }
 
-    fn set_max_size (&self, width: i32, height: i32) -> Status {
-        unsafe {
-            (*self.view()).max_width = width;
-            (*self.view()).max_height = height;
-        }
-        Status::Success
+    /// Clears the log of redisplays returned by
+    /// [`MockBackend::damage_log()`](struct.MockBackend.html#method.damage_log)
+    pub fn clear_damage_log(&mut self) {
+        mock(self.view()).damage_log.clear();
     }
 
-    fn set_aspect_ratio(&self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Status {
-        unsafe {
-            (*self.view()).min_aspect_x = min_x;
-            (*self.view()).max_aspect_x = max_x;
-            (*self.view()).min_aspect_y = min_y;
-            (*self.view()).max_aspect_y = max_y;
+    /// Replays a recording, e.g. read by [`read_recording()`](fn.read_recording.html)
+    ///
+    /// Before each record is replayed, the virtual clock is advanced
+    /// to the recorded time by [`advance_time()`](#method.advance_time),
+    /// so the timers of the view fire when they fall due between the
+    /// records. Recorded timer events of timers that are running in
+    /// the view are skipped, as these timers fire by themselves.
+    /// Redisplays posted by the UI are exposed by the next call of
+    /// `update()`.
+    pub fn replay_recording(&mut self, records: &[Record]) {
+        for record in records {
+            let delay = record.time - mock(self.view()).time;
+            if delay > 0. {
+                self.advance_time(delay);
+            }
+            if let RecordedEvent::Timer(id) = record.event {
+                if mock(self.view()).timers.contains_key(&id) {
+                    continue;
+                }
+            }
+            self.replay(record);
         }
-        Status::Success
     }
 
-    fn make_resizable(&self) -> Status {
-        unsafe {
-            (*self.view()).resizable = true;
+    /// Advances the virtual clock of the mock by `seconds`
+    ///
+    /// The timers started by `start_timer()` that become due meanwhile
+    /// are fired by calling `timer_event()` in the order of their due
+    /// times, timers due at the same time in the order of their ids.
+    /// Like with pugl, timers keep repeating until they are stopped.
+    ///
+    /// Panics if `seconds` is not finite.
+    pub fn advance_time(&mut self, seconds: f64) {
+        assert!(seconds.is_finite(), "cannot advance the time by {}", seconds);
+        let end = mock(self.view()).time + seconds;
+        while let Some(id) = mock(self.view()).next_due_timer(end) {
+            dispatch::<T>(self.view(), ViewEvent::Timer(id));
         }
-        Status::Success
+        mock(self.view()).time = end;
     }
+}
 
-    fn set_window_title (&self, title: &str) -> Status {
-        unsafe {
-            (*self.view()).window_title = title.to_string();
-        }
-        Status::Success
+/// Scripting of user input
+///
+/// These methods queue the events a user would cause, to be
+/// dispatched by the next calls of `update()`. The events are spaced
+/// by a millisecond, starting at the current time of the virtual
+/// clock, so that every scripted event is later than the previous
+/// one. Thus two clicks at the same position in a row make a double
+/// click, unless the clock is advanced in between.
+///
+/// The mock keeps track of the position of the scripted pointer and
+/// generates `PointerIn` and `PointerOut` events as the pointer
+/// enters or leaves the view.
+impl<T: PuglViewTrait> PuglView<T, MockBackend> {
+    /// Moves the pointer to `at`
+    pub fn hover(&mut self, at: Coord) {
+        mock(self.view()).move_pointer(at);
     }
 
-    fn realize(&self) -> Status {
-        let view = unsafe { &mut (*self.view()) };
-	if view.default_width * view.default_height == 0 {
-	    return Status::BadConfiguration
-	}
-	view.realized = true;
-	view.frame.size.w = view.default_width as f64;
-	view.frame.size.h = view.default_height as f64;
-        Status::Success
+    /// Clicks the left mouse button at `at`
+    pub fn click(&mut self, at: Coord) {
+        let mock = mock(self.view());
+        mock.move_pointer(at);
+        mock.queue_button(Button::Left, true, at);
+        mock.queue_button(Button::Left, false, at);
     }
 
-    fn show_window(&self) -> Status {
-	let view = unsafe {
-	    &mut (*self.view())
-	};
-
-	let status = if !view.realized {
-	    self.realize()
-	} else {
-	    Status::Success
-	};
-
-	if status == Status::Success {
-	    view.visible = true
-	}
-
-	status
-    }
-
-    fn hide_window(&self) -> Status {
-        unsafe {
-            (*self.view()).visible = false;
+    /// Drags with the left mouse button from `from` to `to` in `steps` motions
+    ///
+    /// The pointer can leave the view during the drag. It is only
+    /// reported leaving the view after the button has been released.
+    pub fn drag(&mut self, from: Coord, to: Coord, steps: u32) {
+        let mock = mock(self.view());
+        mock.move_pointer(from);
+        mock.queue_button(Button::Left, true, from);
+        let steps = steps.max(1);
+        for step in 1..=steps {
+            let pos = from + (to - from).scale(f64::from(step) / f64::from(steps));
+            mock.queue_input(EventType::MouseMove(MotionContext::default()), pos);
         }
-        Status::Success
-    }
-
-    fn is_visible(&self) -> bool {
-        unsafe { (*self.view()).visible }
+        mock.script_pointer = to;
+        mock.queue_button(Button::Left, false, to);
+        mock.move_pointer(to);
     }
 
-    fn set_cursor(&self, c: Cursor) -> Status {
-        unsafe {
-            (*self.view()).cursor = c;
+    /// Types `text` by pressing and releasing a key for each character
+    ///
+    /// Uppercase letters and the shifted symbols of a US keyboard
+    /// layout are typed like on a keyboard: the left shift key is held
+    /// while the unshifted key is pressed with `Modifiers::SHIFT`. The
+    /// key codes are 0.
+    pub fn type_text(&mut self, text: &str) {
+        let mock = mock(self.view());
+        for c in text.chars() {
+            match unshifted(c) {
+                Some(key) => {
+                    let shift = KeyVal::Special(SpecialKey::ShiftL);
+                    mock.queue_key(shift, Modifiers::NONE, true);
+                    mock.queue_key(KeyVal::Character(key), Modifiers::SHIFT, true);
+                    mock.queue_key(KeyVal::Character(key), Modifiers::SHIFT, false);
+                    mock.queue_key(shift, Modifiers::SHIFT, false);
+                }
+                None => {
+                    mock.queue_key(KeyVal::Character(c), Modifiers::NONE, true);
+                    mock.queue_key(KeyVal::Character(c), Modifiers::NONE, false);
+                }
+            }
         }
-        Status::Success
     }
 
-    fn update(&mut self, timeout: f64) -> Status {
-        let view = unsafe {
-            &mut (*self.view())
-        };
-        view.update_timeout = Some(timeout);
-        if let Some(ev) = view.event_queue.pop_front() {
-            //eprintln!("Issuing event {:?}", ev);
-            self.event(ev);
+    /// Presses and releases `shortcut`
+    ///
+    /// The left keys of the modifiers of the shortcut are pressed
+    /// before the key and released after it in reverse order.
+    pub fn press(&mut self, shortcut: Shortcut) {
+        let mock = mock(self.view());
+        let modifier_keys: Vec<(Modifiers, SpecialKey)> = [
+            (Modifiers::CTRL, SpecialKey::CtrlL),
+            (Modifiers::SHIFT, SpecialKey::ShiftL),
+            (Modifiers::ALT, SpecialKey::AltL),
+            (Modifiers::SUPER, SpecialKey::SuperL),
+        ].iter().cloned().filter(|&(modifier, _)| shortcut.modifiers().contains(modifier)).collect();
+
+        let mut held = Modifiers::NONE;
+        for &(modifier, key) in modifier_keys.iter() {
+            mock.queue_key(KeyVal::Special(key), held, true);
+            held |= modifier;
+        }
+        mock.queue_key(shortcut.key(), held, true);
+        mock.queue_key(shortcut.key(), held, false);
+        for &(modifier, key) in modifier_keys.iter().rev() {
+            mock.queue_key(KeyVal::Special(key), held, false);
+            held -= modifier;
         }
-        Status::Success
     }
 
-    fn start_timer(&self, id: usize, timeout: f64) -> Status {
-        unsafe {
-            (*self.view()).timer_time.insert(id, timeout);
-        }
-        Status::Success
+    /// Scrolls vertically by `dy` lines at `at`, positive values scroll up
+    pub fn scroll(&mut self, at: Coord, dy: f64) {
+        let mock = mock(self.view());
+        mock.move_pointer(at);
+        let direction = if dy < 0. { ScrollDirection::Down } else { ScrollDirection::Up };
+        let scroll = Scroll { dx: 0., dy, modifiers: Modifiers::NONE, direction, phase: ScrollPhase::None };
+        mock.queue_input(EventType::Scroll(scroll), at);
     }
+}
 
-    fn stop_timer(&self, id: usize) -> Status {
-        match unsafe {
-            (*self.view()).timer_time.remove(&id)
-        } {
-            None => Status::Failure,
-            Some(_) => Status::Success
-        }
+/// The symbols typed with shift on a US keyboard layout and their unshifted keys
+const SHIFTED_SYMBOLS: &[(char, char)] = &[
+    ('~', '`'), ('!', '1'), ('@', '2'), ('#', '3'), ('$', '4'), ('%', '5'), ('^', '6'),
+    ('&', '7'), ('*', '8'), ('(', '9'), (')', '0'), ('_', '-'), ('+', '='), ('{', '['),
+    ('}', ']'), ('|', '\\'), (':', ';'), ('"', '\''), ('<', ','), ('>', '.'), ('?', '/'),
+];
+
+/// Returns the key typed with shift to get `c`, if `c` needs shift
+fn unshifted(c: char) -> Option<char> {
+    if c.is_ascii_uppercase() {
+        return Some(c.to_ascii_lowercase());
     }
+    SHIFTED_SYMBOLS.iter().find(|&&(shifted, _)| shifted == c).map(|&(_, key)| key)
 }
 
+fn mock<'a>(view: PuglViewFFI) -> &'a mut MockBackend {
+    unsafe {
+        (*view).backend.as_any_mut().downcast_mut::<MockBackend>()
+            .expect("view is not driven by the mock")
+    }
+}
 
-pub struct PuglViewMock {
-    ui_ptr: *mut std::ffi::c_void,
+/// A redisplay requested from the [`MockBackend`](struct.MockBackend.html)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Damage {
+    /// The entire view by `post_redisplay()`
+    View,
+    /// A part of the view by `post_redisplay_rect()`
+    Rect(Rect)
+}
 
+/// A backend for testing UIs without a window system
+///
+/// A [`PuglView`](struct.PuglView.html) running on the `MockBackend`
+/// does not open a window. Instead the test queues up events that are
+/// dispatched to the UI by [`update()`](trait.PuglViewTrait.html#method.update)
+/// and inspects the state of the "window" by
+/// [`mock_instance()`](struct.PuglView.html#method.mock_instance).
+///
+/// The UI draws into an in-memory image, see [`surface()`](#method.surface).
+/// After a redisplay has been posted, the next `update()` without
+/// pending input events exposes the damaged area.
+///
+/// ```
+/// use pugl_sys::*;
+///
+/// struct UI { view: PuglViewFFI }
+///
+/// impl PuglViewTrait for UI {
+///     fn event(&mut self, _ev: Event) -> Status { Status::Success }
+///     fn exposed(&mut self, _expose: &ExposeArea, _cr: &cairo::Context) {}
+///     fn resize(&mut self, _size: Size) {}
+///     fn close_request(&mut self) {}
+///     fn view(&self) -> PuglViewFFI { self.view }
+/// }
+///
+/// let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |view| UI { view });
+/// view.handle().request_attention();
+/// assert!(view.mock_instance().attention_requested());
+/// ```
+pub struct MockBackend {
     frame: Rect,
 
     default_width: i32,
@@ -258,29 +339,113 @@ pub struct PuglViewMock {
     min_aspect_y: i32,
     max_aspect_y: i32,
 
-    resizable: bool,
+    hints: HashMap<p::PuglViewHint, p::PuglViewHintValue>,
 
     window_title: String,
+    window_icon: Vec<WindowIcon>,
+    window_state: WindowState,
 
     visible: bool,
     realized: bool,
+    attention_requested: bool,
+    pointer_grabbed: bool,
 
     cursor: Cursor,
+    cursor_hidden: bool,
+    pointer_position: Coord,
+
+    monitors: Vec<Monitor>,
 
     update_timeout: Option<f64>,
+    update_count: usize,
+    update_phase: UpdatePhase,
 
     event_queue: VecDeque<Event>,
+    last_input_time: f64,
+    script_pointer: Coord,
+    script_pointer_inside: bool,
+
+    damage_log: Vec<Damage>,
+    pending_expose: Option<Rect>,
+    surface: Option<cairo::ImageSurface>,
+    scale_factor: f64,
 
-    timer_time: std::collections::HashMap<usize, f64>,
+    time: f64,
+    timers: HashMap<usize, MockTimer>,
 }
 
+/// The shortest timer period of the mock in seconds, shorter ones are rounded up
+const MIN_TIMER_PERIOD: f64 = 0.001;
+
+/// The progress of the mock through a call of `update()`
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum UpdatePhase {
+    /// Dispatching the queued events and then the timers due until the given time
+    Events(f64),
+    /// Exposing the damage posted until now
+    Expose,
+    /// Nothing left to do until the next `update()`
+    Done
+}
 
+/// How `update()` has been told to wait for events
+///
+/// See [`MockBackend::update_mode()`](struct.MockBackend.html#method.update_mode).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum UpdateMode {
+    /// With a timeout of zero, returning immediately as plugins should do
+    Polling,
+    /// With a negative timeout, waiting until an event occurs
+    Blocking,
+    /// With a positive timeout, processing events for that many seconds
+    Timeout(f64)
+}
 
+impl From<f64> for UpdateMode {
+    fn from(timeout: f64) -> UpdateMode {
+        if timeout == 0. {
+            UpdateMode::Polling
+        } else if timeout < 0. {
+            UpdateMode::Blocking
+        } else {
+            UpdateMode::Timeout(timeout)
+        }
+    }
+}
+
+/// The time between two scripted input events in seconds
+const INPUT_INTERVAL: f64 = 0.001;
 
-impl Default for PuglViewMock {
+struct MockTimer {
+    period: f64,
+    due: f64
+}
+
+/// The former name of [`MockBackend`](struct.MockBackend.html)
+pub type PuglViewMock = MockBackend;
+
+impl Default for MockBackend {
     fn default() -> Self {
+        let hints = [
+            (p::PuglViewHint_PUGL_USE_COMPAT_PROFILE, p::PuglViewHintValue_PUGL_TRUE),
+            (p::PuglViewHint_PUGL_USE_DEBUG_CONTEXT, p::PuglViewHintValue_PUGL_FALSE),
+            (p::PuglViewHint_PUGL_CONTEXT_VERSION_MAJOR, 2),
+            (p::PuglViewHint_PUGL_CONTEXT_VERSION_MINOR, 0),
+            (p::PuglViewHint_PUGL_RED_BITS, 8),
+            (p::PuglViewHint_PUGL_GREEN_BITS, 8),
+            (p::PuglViewHint_PUGL_BLUE_BITS, 8),
+            (p::PuglViewHint_PUGL_ALPHA_BITS, 8),
+            (p::PuglViewHint_PUGL_DEPTH_BITS, 0),
+            (p::PuglViewHint_PUGL_STENCIL_BITS, 0),
+            (p::PuglViewHint_PUGL_SAMPLES, 0),
+            (p::PuglViewHint_PUGL_DOUBLE_BUFFER, p::PuglViewHintValue_PUGL_TRUE),
+            (p::PuglViewHint_PUGL_SWAP_INTERVAL, p::PuglViewHintValue_PUGL_DONT_CARE),
+            (p::PuglViewHint_PUGL_RESIZABLE, p::PuglViewHintValue_PUGL_FALSE),
+            (p::PuglViewHint_PUGL_IGNORE_KEY_REPEAT, p::PuglViewHintValue_PUGL_TRUE),
+            (p::PuglViewHint_PUGL_REFRESH_RATE, p::PuglViewHintValue_PUGL_DONT_CARE),
+        ].iter().cloned().collect();
+
         let mock = Self {
-            ui_ptr: std::ptr::null_mut(),
             frame: Default::default(),
 
             default_width: Default::default(),
@@ -297,40 +462,533 @@ impl Default for PuglViewMock {
             min_aspect_y: Default::default(),
             max_aspect_y: Default::default(),
 
-            resizable: Default::default(),
+            hints,
 
             window_title: Default::default(),
+            window_icon: Default::default(),
+            window_state: Default::default(),
 
             visible: false,
-	    realized: false,
+            realized: false,
+            attention_requested: false,
+            pointer_grabbed: false,
 
             cursor: Default::default(),
+            cursor_hidden: false,
+            pointer_position: Default::default(),
+
+            monitors: vec![Monitor {
+                name: "mock".to_string(),
+                geometry: Rect { pos: Coord { x: 0., y: 0. }, size: Size { w: 1920., h: 1080. } },
+                primary: true
+            }],
 
             update_timeout: Default::default(),
+            update_count: 0,
+            update_phase: UpdatePhase::Done,
 
             event_queue: VecDeque::new(),
+            last_input_time: f64::NEG_INFINITY,
+            script_pointer: Coord::default(),
+            script_pointer_inside: false,
 
-            timer_time: Default::default(),
+            damage_log: Vec::new(),
+            pending_expose: None,
+            surface: None,
+            scale_factor: 1.0,
+
+            time: 0.0,
+            timers: Default::default(),
 
         };
         mock
     }
 }
 
-impl PuglViewMock {
-    fn queue_event(&mut self, ev: Event) {
+impl MockBackend {
+    fn queue_event(&mut self, mut ev: Event) {
+        ev.context.time = self.time;
         self.event_queue.push_back(ev);
     }
 
+    /// Queues a scripted input event at `pos` a bit later than the previous one
+    fn queue_input(&mut self, data: EventType, pos: Coord) {
+        let time = self.time.max(self.last_input_time + INPUT_INTERVAL);
+        self.last_input_time = time;
+        self.event_queue.push_back(Event {
+            data,
+            context: EventContext {
+                pos,
+                pos_root: pos + self.frame.pos,
+                time,
+                flags: EventFlags::NONE
+            }
+        });
+    }
+
+    fn queue_button(&mut self, button: Button, press: bool, pos: Coord) {
+        let button = MouseButton { button, modifiers: Modifiers::NONE, click_count: 1 };
+        let data = if press {
+            EventType::MouseButtonPress(button)
+        } else {
+            EventType::MouseButtonRelease(button)
+        };
+        self.queue_input(data, pos);
+    }
+
+    fn queue_key(&mut self, key: KeyVal, modifiers: Modifiers, press: bool) {
+        let key = Key { key, modifiers, code: 0, is_repeat: false };
+        let data = if press { EventType::KeyPress(key) } else { EventType::KeyRelease(key) };
+        self.queue_input(data, self.script_pointer);
+    }
+
+    /// Moves the scripted pointer to `pos`, crossing the border of the view if necessary
+    fn move_pointer(&mut self, pos: Coord) {
+        let view = self.view_rect();
+        let inside = pos.x >= 0. && pos.y >= 0. && pos.x < view.size.w && pos.y < view.size.h;
+        match (self.script_pointer_inside, inside) {
+            (false, true) => {
+                self.queue_input(EventType::PointerIn(CrossingMode::Normal), pos);
+                self.queue_input(EventType::MouseMove(MotionContext::default()), pos);
+            }
+            (true, true) if self.script_pointer != pos => {
+                self.queue_input(EventType::MouseMove(MotionContext::default()), pos);
+            }
+            (true, false) => self.queue_input(EventType::PointerOut(CrossingMode::Normal), pos),
+            _ => {}
+        }
+        self.script_pointer = pos;
+        self.script_pointer_inside = inside;
+    }
+
+    fn view_rect(&self) -> Rect {
+        Rect { pos: Coord::default(), size: self.frame.size }
+    }
+
+    fn post_expose(&mut self, area: Rect) {
+        self.pending_expose = Some(match self.pending_expose {
+            Some(pending) => pending.union(&area),
+            None => area
+        });
+    }
+
+    /// Returns the event to expose `area`, making sure there is a surface of the view's size
+    fn expose(&mut self, area: Rect) -> Option<ViewEvent> {
+        let width = (self.frame.size.w * self.scale_factor).round() as i32;
+        let height = (self.frame.size.h * self.scale_factor).round() as i32;
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        let area = match &self.surface {
+            Some(surface) if surface.get_width() == width && surface.get_height() == height => {
+                area.intersection(&self.view_rect())?
+            }
+            _ => {
+                self.surface = Some(cairo::ImageSurface::create(cairo::Format::ARgb32, width, height).ok()?);
+                self.view_rect()
+            }
+        };
+        let cr = cairo::Context::new(self.surface.as_ref()?);
+        cr.scale(self.scale_factor, self.scale_factor);
+        cr.rectangle(area.pos.x, area.pos.y, area.size.w, area.size.h);
+        cr.clip();
+        Some(ViewEvent::Expose(ExposeArea { pos: area.pos, size: area.size }, cr))
+    }
+
+    /// Realizes the view, which needs a default size
+    fn realize_now(&mut self) -> Status {
+        if self.default_width * self.default_height == 0 {
+            return Status::BadConfiguration
+        }
+        self.realized = true;
+        self.frame.size.w = self.default_width as f64;
+        self.frame.size.h = self.default_height as f64;
+        Status::Success
+    }
+
+    /// Returns the id of the timer due next until `end` and reschedules it
+    fn next_due_timer(&mut self, end: f64) -> Option<usize> {
+        let (&id, timer) = self.timers.iter_mut()
+            .filter(|(_, timer)| timer.due <= end)
+            .min_by(|(id_a, a), (id_b, b)| a.due.total_cmp(&b.due).then(id_a.cmp(id_b)))?;
+        self.time = self.time.max(timer.due);
+        timer.due += timer.period;
+        Some(id)
+    }
+
+    /// Returns the current time of the virtual clock in seconds
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     pub fn min_size(&self) -> Size {
         Size { w: self.min_width as f64, h: self.min_height as f64 }
     }
+
+    /// Returns the size set by `set_default_size()`
+    pub fn default_size(&self) -> Size {
+        Size { w: self.default_width as f64, h: self.default_height as f64 }
+    }
+
+    /// Returns the size set by `set_max_size()`
+    pub fn max_size(&self) -> Size {
+        Size { w: self.max_width as f64, h: self.max_height as f64 }
+    }
+
+    /// Returns the aspect ratio range set by `set_aspect_ratio()`
+    /// as `(min_x, min_y, max_x, max_y)`
+    pub fn aspect_ratio(&self) -> (i32, i32, i32, i32) {
+        (self.min_aspect_x, self.min_aspect_y, self.max_aspect_x, self.max_aspect_y)
+    }
+
+    /// Returns true iff the view has been made resizable
+    pub fn resizable(&self) -> bool {
+        self.view_hint(p::PuglViewHint_PUGL_RESIZABLE) == p::PuglViewHintValue_PUGL_TRUE
+    }
+
+    /// Returns the window title
+    pub fn window_title(&self) -> &str {
+        &self.window_title
+    }
+
+    /// Returns the icons of the window
+    pub fn window_icon(&self) -> &[WindowIcon] {
+        &self.window_icon
+    }
+
+    /// Returns the cursor set by `set_cursor()`
+    pub fn cursor(&self) -> Cursor {
+        self.cursor
+    }
+
+    /// Returns true iff the view has been realized
+    pub fn realized(&self) -> bool {
+        self.realized
+    }
+
+    /// Returns the ids and the periods of the running timers ordered by id
+    pub fn pending_timers(&self) -> Vec<(usize, f64)> {
+        let mut timers: Vec<(usize, f64)> = self.timers.iter()
+            .map(|(&id, timer)| (id, timer.period))
+            .collect();
+        timers.sort_by_key(|&(id, _)| id);
+        timers
+    }
+
+    /// Returns the timeout `update()` has been called with last time,
+    /// `None` if it has not been called yet
+    pub fn update_timeout(&self) -> Option<f64> {
+        self.update_timeout
+    }
+
+    /// Returns how the last call of `update()` waited for events,
+    /// `None` if it has not been called yet
+    ///
+    /// Useful to check that a plugin UI never blocks the host.
+    pub fn update_mode(&self) -> Option<UpdateMode> {
+        self.update_timeout.map(UpdateMode::from)
+    }
+
+    /// Returns how often `update()` has been called
+    pub fn update_count(&self) -> usize {
+        self.update_count
+    }
+
+    /// Returns the redisplays requested since the view has been
+    /// created or the log has been cleared
+    pub fn damage_log(&self) -> &[Damage] {
+        &self.damage_log
+    }
+
+    /// Returns the image the UI has drawn on, `None` if it has not been exposed yet
+    ///
+    /// The surface has the format `ARgb32` and the size of the view
+    /// multiplied by the scale factor of the last
+    /// [`render()`](struct.PuglView.html#method.render), which is 1 by default.
+    pub fn surface(&self) -> Option<&cairo::ImageSurface> {
+        self.surface.as_ref()
+    }
+
+    /// Returns the color of the pixel at `x`, `y` of the surface as ARGB
+    ///
+    /// Returns `None` if the view has not been exposed yet or the
+    /// pixel is outside of the surface.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<u32> {
+        let surface = self.surface.as_ref()?;
+        let width = surface.get_width();
+        if x < 0 || y < 0 || x >= width || y >= surface.get_height() {
+            return None;
+        }
+        surface_pixels(surface)?.get((y * width + x) as usize).copied()
+    }
+
+    /// Saves the image the UI has drawn on as PNG file
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), cairo::IoError> {
+        let surface = self.surface.as_ref().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "the view has not been exposed yet")
+        })?;
+        let mut file = std::fs::File::create(path)?;
+        surface.write_to_png(&mut file)
+    }
+
+    /// Returns true iff attention has been requested since the view had the focus last time
+    pub fn attention_requested(&self) -> bool {
+        self.attention_requested
+    }
+
+    /// Returns true iff the cursor is hidden by the relative pointer mode
+    pub fn cursor_hidden(&self) -> bool {
+        self.cursor_hidden
+    }
+
+    /// Returns the position the pointer has been warped to last time
+    pub fn pointer_position(&self) -> Coord {
+        self.pointer_position
+    }
+
+    /// Returns true iff the pointer is grabbed
+    pub fn pointer_grabbed(&self) -> bool {
+        self.pointer_grabbed
+    }
+}
+
+impl Backend for MockBackend {}
+
+impl BackendImpl for MockBackend {
+    fn new<T: PuglViewTrait>(_parent_window: *mut std::ffi::c_void) -> MockBackend {
+        MockBackend::default()
+    }
+
+    fn set_handle(&mut self, _view: PuglViewFFI) {}
+
+    fn world(&self) -> *mut p::PuglWorld {
+        std::ptr::null_mut::<p::PuglWorld>()
+    }
+
+    fn native_window(&self) -> p::PuglNativeView {
+        0
+    }
+
+    fn is_realized(&self) -> bool {
+        self.realized
+    }
+
+    fn post_redisplay(&mut self) -> Status {
+        self.damage_log.push(Damage::View);
+        self.post_expose(self.view_rect());
+        Status::Success
+    }
+
+    fn post_redisplay_rect(&mut self, rect: Rect) -> Status {
+        self.damage_log.push(Damage::Rect(rect));
+        self.post_expose(rect);
+        Status::Success
+    }
+
+    fn frame(&self) -> Rect {
+        self.frame
+    }
+
+    fn set_frame(&mut self, frame: Rect) -> Status {
+        self.frame = frame;
+        Status::Success
+    }
+
+    fn monitors(&self) -> Vec<Monitor> {
+        self.monitors.clone()
+    }
+
+    fn set_default_size(&mut self, width: i32, height: i32) -> Status {
+        self.default_width = width;
+        self.default_height = height;
+        Status::Success
+    }
+
+    fn set_min_size(&mut self, width: i32, height: i32) -> Status {
+        self.min_width = width;
+        self.min_height = height;
+        Status::Success
+    }
+
+    fn set_max_size(&mut self, width: i32, height: i32) -> Status {
+        self.max_width = width;
+        self.max_height = height;
+        Status::Success
+    }
+
+    fn set_aspect_ratio(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Status {
+        self.min_aspect_x = min_x;
+        self.max_aspect_x = max_x;
+        self.min_aspect_y = min_y;
+        self.max_aspect_y = max_y;
+        Status::Success
+    }
+
+    fn view_hint(&self, hint: p::PuglViewHint) -> p::PuglViewHintValue {
+        match self.hints.get(&hint) {
+            Some(&value) => value,
+            None => p::PuglViewHintValue_PUGL_DONT_CARE
+        }
+    }
+
+    fn set_view_hint(&mut self, hint: p::PuglViewHint, value: p::PuglViewHintValue) -> Status {
+        self.hints.insert(hint, value);
+        Status::Success
+    }
+
+    fn set_window_title(&mut self, title: &str) -> Status {
+        self.window_title = title.to_string();
+        Status::Success
+    }
+
+    fn set_window_icon(&mut self, icons: &[WindowIcon]) -> Status {
+        self.window_icon = icons.to_vec();
+        Status::Success
+    }
+
+    fn set_window_state(&mut self, state: WindowState, enable: bool) -> Status {
+        self.window_state.set(state, enable);
+        Status::Success
+    }
+
+    fn window_state(&self) -> Option<WindowState> {
+        Some(self.window_state)
+    }
+
+    fn realize(&mut self) -> DeferredCall {
+        let status = self.realize_now();
+        Box::new(move || status)
+    }
+
+    fn show_window(&mut self) -> DeferredCall {
+        let status = if !self.realized {
+            self.realize_now()
+        } else {
+            Status::Success
+        };
+
+        if status == Status::Success {
+            self.visible = true
+        }
+
+        Box::new(move || status)
+    }
+
+    fn hide_window(&mut self) -> Status {
+        self.visible = false;
+        Status::Success
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn request_attention(&mut self) -> Status {
+        self.attention_requested = true;
+        Status::Success
+    }
+
+    fn grab_pointer(&mut self) -> Status {
+        if !self.realized {
+            return Status::Failure;
+        }
+        self.pointer_grabbed = true;
+        Status::Success
+    }
+
+    fn ungrab_pointer(&mut self) -> Status {
+        self.pointer_grabbed = false;
+        Status::Success
+    }
+
+    fn set_pointer_position(&mut self, pos: Coord) -> Status {
+        if !self.realized {
+            return Status::Failure;
+        }
+        self.pointer_position = pos;
+        Status::Success
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Status {
+        self.cursor_hidden = !visible;
+        Status::Success
+    }
+
+    fn set_cursor(&mut self, c: Cursor) -> Status {
+        self.cursor = c;
+        Status::Success
+    }
+
+    fn time(&self) -> f64 {
+        self.time
+    }
+
+    fn update(&mut self, timeout: f64) -> DeferredCall {
+        self.update_timeout = Some(timeout);
+        self.update_count += 1;
+        let end = match UpdateMode::from(timeout) {
+            UpdateMode::Polling => self.time,
+            UpdateMode::Timeout(timeout) => self.time + timeout,
+            UpdateMode::Blocking if self.event_queue.is_empty() && self.pending_expose.is_none() => {
+                // Wait for the next timer, there is nothing else that could wake us up
+                let next_due = self.timers.values().map(|timer| timer.due).fold(f64::INFINITY, f64::min);
+                if next_due.is_finite() { next_due } else { self.time }
+            }
+            UpdateMode::Blocking => self.time
+        };
+        self.update_phase = UpdatePhase::Events(end);
+        Box::new(|| Status::Success)
+    }
+
+    /// Like pugl, the mock first dispatches all the queued events,
+    /// then fires the timers that become due during `update()` and
+    /// finally exposes what has been posted to be redisplayed.
+    fn next_event(&mut self) -> Option<ViewEvent> {
+        if let UpdatePhase::Events(end) = self.update_phase {
+            if let Some(ev) = self.event_queue.pop_front() {
+                self.time = self.time.max(ev.context.time);
+                return Some(ViewEvent::Input(ev));
+            }
+            if let Some(id) = self.next_due_timer(end) {
+                return Some(ViewEvent::Timer(id));
+            }
+            self.time = self.time.max(end);
+            self.update_phase = UpdatePhase::Expose;
+        }
+        if self.update_phase == UpdatePhase::Expose {
+            self.update_phase = UpdatePhase::Done;
+            let area = self.pending_expose.take()?;
+            return self.expose(area);
+        }
+        None
+    }
+
+    fn start_timer(&mut self, id: usize, timeout: f64) -> Status {
+        if !timeout.is_finite() {
+            return Status::BadParameter;
+        }
+        let period = timeout.max(MIN_TIMER_PERIOD);
+        self.timers.insert(id, MockTimer { period, due: self.time + period });
+        Status::Success
+    }
+
+    fn stop_timer(&mut self, id: usize) -> Status {
+        match self.timers.remove(&id) {
+            None => Status::Failure,
+            Some(_) => Status::Success
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
 }
 
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::shortcut::Shortcut;
 
     #[derive(PartialEq, Debug)]
     enum ClickState {
@@ -343,7 +1001,17 @@ mod test {
         view: PuglViewFFI,
 
         click_state: ClickState,
+        click_count: u32,
+        relative_motion: Coord,
+        last_key: Option<Key>,
+        shortcut: Option<usize>,
         pointer_entered: bool,
+        window_state: WindowState,
+        timer_events: Vec<usize>,
+        event_times: Vec<f64>,
+        events: Vec<EventType>,
+        calls: Vec<&'static str>,
+        exposed: Vec<ExposeArea>,
     }
 
 
@@ -352,7 +1020,17 @@ mod test {
             Self {
                 view,
                 click_state: ClickState::None,
-                pointer_entered: false
+                click_count: 0,
+                relative_motion: Coord::default(),
+                last_key: None,
+                shortcut: None,
+                pointer_entered: false,
+                window_state: WindowState::NONE,
+                timer_events: Vec::new(),
+                event_times: Vec::new(),
+                events: Vec::new(),
+                calls: Vec::new(),
+                exposed: Vec::new()
             }
         }
     }
@@ -360,22 +1038,63 @@ mod test {
     impl PuglViewTrait for UI {
         fn view(&self) -> PuglViewFFI { self.view }
 
+        fn exposed(&mut self, expose: &ExposeArea, cr: &cairo::Context) {
+            self.exposed.push(*expose);
+            self.calls.push("exposed");
+            cr.set_source_rgb(1., 0., 0.);
+            cr.paint();
+            cr.set_source_rgb(0., 0., 1.);
+            cr.rectangle(0., 0., 8., 8.);
+            cr.fill();
+        }
+
+        fn resize(&mut self, _size: Size) {}
+
+        fn close_request(&mut self) {}
+
         fn event(&mut self, ev: Event) -> Status {
+            self.event_times.push(ev.context.time);
+            self.events.push(ev.data);
+            self.calls.push("event");
             match ev.data {
-                EventType::MouseButtonPress(_) => self.click_state = ClickState::Clicked,
+                EventType::MouseButtonPress(b) => {
+                    self.click_state = ClickState::Clicked;
+                    self.click_count = b.click_count;
+                }
                 EventType::MouseButtonRelease(_) => self.click_state = ClickState::Released,
-                EventType::PointerIn => self.pointer_entered = true,
-                EventType::PointerOut => self.pointer_entered = false,
+                EventType::RelativeMove(motion) => self.relative_motion += motion.delta,
+                EventType::KeyPress(key) => self.last_key = Some(key),
+                EventType::PointerIn(_) => self.pointer_entered = true,
+                EventType::PointerOut(CrossingMode::Normal) => self.pointer_entered = false,
                 _ => {}
             }
 
             Status::Success
         }
+
+        fn window_state_changed(&mut self, state: WindowState) -> Status {
+            self.window_state = state;
+            Status::Success
+        }
+
+        fn shortcut_event(&mut self, id: usize) -> Status {
+            self.shortcut = Some(id);
+            Status::Success
+        }
+
+        fn timer_event(&mut self, id: usize) -> Status {
+            self.timer_events.push(id);
+            self.calls.push("timer_event");
+            if id == 3 {
+                self.stop_timer(3);
+            }
+            Status::Success
+        }
     }
 
 
-    fn make_view() -> Box<PuglView<UI>> {
-        let view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+    fn make_view() -> Box<PuglView<UI, MockBackend>> {
+        let view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         view
     }
 
@@ -388,7 +1107,7 @@ mod test {
 
     #[test]
     fn show_window_no_size() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         let ui = view.handle();
         assert_eq!(ui.show_window(), Status::BadConfiguration);
         assert!(!ui.is_visible());
@@ -396,19 +1115,19 @@ mod test {
 
     #[test]
     fn show_window_default_size() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         let ui = view.handle();
         ui.set_default_size(32, 16);
         assert_eq!(ui.show_window(), Status::Success);
         assert!(ui.is_visible());
-        let size = unsafe { (*ui.view()).frame.size };
+        let size = ui.get_frame().size;
         assert_eq!(size.w, 32.0);
         assert_eq!(size.h, 16.0);
     }
 
     #[test]
     fn hide_window() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         let ui = view.handle();
         ui.set_default_size(32, 16);
         ui.show_window();
@@ -418,7 +1137,7 @@ mod test {
 
     #[test]
     fn show_resize_hide_show() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         {
             let ui = view.handle();
             ui.set_default_size(32, 16);
@@ -429,14 +1148,14 @@ mod test {
         ui.hide_window();
         ui.show_window();
 
-        let size = unsafe { (*ui.view()).frame.size };
+        let size = ui.get_frame().size;
         assert_eq!(size.w, 42.0);
         assert_eq!(size.h, 23.0);
     }
 
     #[test]
     fn mouse_click_event() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         {
             let ui = view.handle();
             ui.set_default_size(32, 16);
@@ -445,55 +1164,808 @@ mod test {
         let event_context = EventContext {
             pos: Coord { x: 16., y: 8.},
             pos_root: Coord { x: 16., y: 8.},
-            time: 0.0
+            time: 0.0,
+            flags: EventFlags::NONE
         };
         let press_event = Event {
-            data: EventType::MouseButtonPress(MouseButton { num: 0, modifiers: Modifiers::default() }),
+            data: EventType::MouseButtonPress(MouseButton { button: Button::Left, modifiers: Modifiers::default(), click_count: 1 }),
             context: event_context
         };
         let release_event = Event {
-            data: EventType::MouseButtonRelease(MouseButton { num: 0, modifiers: Modifiers::default() }),
+            data: EventType::MouseButtonRelease(MouseButton { button: Button::Left, modifiers: Modifiers::default(), click_count: 1 }),
             context: event_context
         };
 
+        assert_eq!(view.handle().click_state, ClickState::None);
         view.queue_event(press_event);
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().click_state, ClickState::Clicked);
         view.queue_event(release_event);
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().click_state, ClickState::Released);
+    }
 
-        let ui = view.handle();
-        assert_eq!(ui.click_state, ClickState::None);
-        ui.update(-1.0);
-        assert_eq!(ui.click_state, ClickState::Clicked);
-        ui.update(-1.0);
-        assert_eq!(ui.click_state, ClickState::Released);
+    #[test]
+    fn mouse_double_click_event() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let button = MouseButton { button: Button::Left, modifiers: Modifiers::default(), click_count: 1 };
+        for count in 1..4 {
+            let context = EventContext::default();
+            view.queue_event(Event { data: EventType::MouseButtonPress(button), context });
+            view.handle().update(0.0);
+            assert_eq!(view.handle().click_count, count);
+            view.queue_event(Event { data: EventType::MouseButtonRelease(button), context });
+            view.advance_time(0.1);
+        }
     }
 
     #[test]
     fn pointer_enter_leave_event() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
-        view.queue_event(Event { data: EventType::PointerIn, context: EventContext::default() });
-        view.queue_event(Event { data: EventType::PointerOut, context: EventContext::default() });
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let crossing = |data| Event { data, context: EventContext::default() };
+        assert!(!view.handle().pointer_entered);
+        view.queue_event(crossing(EventType::PointerIn(CrossingMode::Normal)));
+        view.handle().update(-1.0);
+        assert!(view.handle().pointer_entered);
+        view.queue_event(crossing(EventType::PointerOut(CrossingMode::Grab)));
+        view.handle().update(-1.0);
+        assert!(view.handle().pointer_entered);
+        view.queue_event(crossing(EventType::PointerOut(CrossingMode::Normal)));
+        view.handle().update(-1.0);
+        assert!(!view.handle().pointer_entered);
+    }
 
+    #[test]
+    fn window_title() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().set_window_title("Test Pugl");
+        assert_eq!(view.mock_instance().window_title(), "Test Pugl");
+    }
+
+    #[test]
+    fn window_icon() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let icon = WindowIcon::from_rgba(1, 1, &[0xff, 0x00, 0x00, 0xff]).unwrap();
+        view.handle().set_window_icon(std::slice::from_ref(&icon));
+        assert_eq!(view.mock_instance().window_icon(), &[icon]);
+    }
+
+    #[test]
+    fn set_window_state() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         let ui = view.handle();
-        assert!(!ui.pointer_entered);
-        ui.update(-1.0);
-        assert!(ui.pointer_entered);
-        ui.update(-1.0);
-        assert!(!ui.pointer_entered);
+        ui.set_window_state(WindowState::MAXIMIZED | WindowState::ABOVE, true);
+        assert_eq!(ui.window_state(), WindowState::MAXIMIZED | WindowState::ABOVE);
+        ui.set_window_state(WindowState::MAXIMIZED, false);
+        assert_eq!(ui.window_state(), WindowState::ABOVE);
     }
 
     #[test]
-    fn window_title() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+    fn window_state_changed() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.fake_window_state(WindowState::FULLSCREEN);
+        let ui = view.handle();
+        assert_eq!(ui.window_state, WindowState::FULLSCREEN);
+        assert_eq!(ui.window_state(), WindowState::FULLSCREEN);
+    }
+
+    #[test]
+    fn requested_window_state_reported_when_observed() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().set_window_state(WindowState::MAXIMIZED, true);
+        assert_eq!(view.handle().window_state, WindowState::NONE);
+
+        // the window manager grants the request
+        view.fake_window_state(WindowState::MAXIMIZED);
+        assert_eq!(view.handle().window_state, WindowState::MAXIMIZED);
+
+        // the window manager refuses the request
+        view.handle().set_window_state(WindowState::FULLSCREEN, true);
+        view.fake_window_state(WindowState::MAXIMIZED);
+        assert_eq!(view.handle().window_state, WindowState::MAXIMIZED);
+        assert_eq!(view.handle().window_state(), WindowState::MAXIMIZED);
+    }
+
+    #[test]
+    fn center_on_screen() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let ui = view.handle();
+        ui.set_default_size(320, 200);
+        ui.realize();
+        assert_eq!(ui.center_on_screen(), Status::Success);
+        assert_eq!(ui.get_frame().pos, Coord { x: 800., y: 440. });
+    }
+
+    #[test]
+    fn center_on_screen_unrealized() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let ui = view.handle();
+        assert_eq!(ui.center_on_screen(), Status::Failure);
+    }
+
+    #[test]
+    fn restore_frame_vanished_monitor() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.fake_monitors(vec![Monitor {
+            name: "small".to_string(),
+            geometry: Rect { pos: Coord { x: 0., y: 0. }, size: Size { w: 1024., h: 768. } },
+            primary: true
+        }]);
+        let ui = view.handle();
+        let saved = Rect { pos: Coord { x: 2000., y: 100. }, size: Size { w: 200., h: 100. } };
+        ui.restore_frame(saved);
+        assert_eq!(ui.get_frame().pos, Coord { x: 412., y: 334. });
+    }
+
+    #[test]
+    fn request_attention() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        assert!(!view.mock_instance().attention_requested());
+        view.handle().request_attention();
+        assert!(view.mock_instance().attention_requested());
+        view.fake_focus_in();
+        assert!(!view.mock_instance().attention_requested());
+    }
+
+    #[test]
+    fn grab_pointer() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        assert_eq!(view.handle().grab_pointer(), Status::Failure);
+        view.handle().set_default_size(42, 23);
+        view.handle().realize();
+        assert_eq!(view.handle().grab_pointer(), Status::Success);
+        assert!(view.mock_instance().pointer_grabbed());
+        view.handle().ungrab_pointer();
+        assert!(!view.mock_instance().pointer_grabbed());
+    }
+
+    #[test]
+    fn relative_pointer_mode() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().set_default_size(42, 23);
+        view.handle().realize();
+        view.queue_event(Event {
+            data: EventType::MouseMove(MotionContext::default()),
+            context: EventContext { pos: Coord { x: 10., y: 10. }, ..Default::default() }
+        });
+        view.handle().update(-1.0);
+
+        assert_eq!(view.handle().set_relative_pointer_mode(true), Status::Success);
+        assert!(view.handle().relative_pointer_mode());
+        assert!(view.mock_instance().cursor_hidden());
+        assert!(view.mock_instance().pointer_grabbed());
+
+        view.queue_event(Event {
+            data: EventType::MouseMove(MotionContext::default()),
+            context: EventContext { pos: Coord { x: 15., y: 8. }, ..Default::default() }
+        });
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().relative_motion, Coord { x: 5., y: -2. });
+        assert_eq!(view.mock_instance().pointer_position(), Coord { x: 10., y: 10. });
+
+        view.handle().set_pointer_position(Coord { x: 30., y: 30. });
+        view.handle().set_relative_pointer_mode(false);
+        assert!(!view.handle().relative_pointer_mode());
+        assert!(!view.mock_instance().cursor_hidden());
+        assert!(!view.mock_instance().pointer_grabbed());
+        assert_eq!(view.mock_instance().pointer_position(), Coord { x: 10., y: 10. });
+    }
+
+    #[test]
+    fn relative_pointer_mode_ends_on_focus_out() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().set_default_size(42, 23);
+        view.handle().realize();
+        view.hover(Coord { x: 10., y: 10. });
+        view.handle().update(0.0);
+
+        view.handle().set_relative_pointer_mode(true);
+        view.hover(Coord { x: 20., y: 10. });
+        view.fake_focus_out();
+        assert!(!view.handle().relative_pointer_mode());
+        assert!(!view.mock_instance().pointer_grabbed());
+        assert!(!view.mock_instance().cursor_hidden());
+        assert_eq!(view.mock_instance().pointer_position(), Coord { x: 10., y: 10. });
+    }
+
+    #[test]
+    fn scroll_gesture_ends() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let smooth = Event {
+            data: EventType::Scroll(Scroll {
+                dx: 0., dy: 0.5,
+                modifiers: Modifiers::NONE,
+                direction: ScrollDirection::Smooth,
+                phase: ScrollPhase::None
+            }),
+            context: EventContext::default()
+        };
+        let phases = |events: &[EventType]| -> Vec<Option<ScrollPhase>> {
+            events.iter().map(|data| match data {
+                EventType::Scroll(scroll) => Some(scroll.phase),
+                _ => None
+            }).collect()
+        };
+
+        view.queue_event(smooth);
+        view.advance_time(0.1);
+        view.queue_event(smooth);
+        view.advance_time(0.05);
+        view.queue_event(Event { data: EventType::MouseMove(MotionContext::default()), context: EventContext::default() });
+        view.handle().update(0.0);
+        let ui = view.handle();
+        assert_eq!(phases(&ui.events), vec![Some(ScrollPhase::Begin), Some(ScrollPhase::Update), Some(ScrollPhase::End), None]);
+        assert_eq!(ui.event_times[2], ui.event_times[3]);
+        ui.events.clear();
+        ui.event_times.clear();
+
+        // ended by time when update() returns
+        view.queue_event(smooth);
+        view.handle().update(0.0);
+        assert_eq!(phases(&view.handle().events), vec![Some(ScrollPhase::Begin)]);
+        view.advance_time(0.2);
+        view.handle().update(0.0);
         let ui = view.handle();
-        ui.set_window_title("Test Pugl");
-        unsafe {
-            assert_eq!((*ui.view()).window_title, "Test Pugl");
+        assert_eq!(phases(&ui.events), vec![Some(ScrollPhase::Begin), Some(ScrollPhase::End)]);
+        assert_eq!(ui.event_times[1] - ui.event_times[0], 0.2);
+    }
+
+    #[test]
+    fn shortcuts() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let redo: Shortcut = "Ctrl+Shift+Z".parse().unwrap();
+        view.handle().add_shortcut(redo, 42);
+
+        let key = |c, modifiers| Key { key: KeyVal::Character(c), modifiers, code: 52, is_repeat: false };
+        let key_event = |c, modifiers| Event {
+            data: EventType::KeyPress(key(c, modifiers)),
+            context: EventContext::default()
+        };
+        view.queue_event(key_event('z', Modifiers::CTRL));
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().shortcut, None);
+        assert!(view.handle().last_key.is_some());
+        view.handle().last_key = None;
+        view.queue_event(Event { data: EventType::KeyRelease(key('z', Modifiers::CTRL)), context: EventContext::default() });
+        view.handle().update(-1.0);
+
+        view.queue_event(key_event('Z', Modifiers::CTRL | Modifiers::SHIFT));
+        let ui = view.handle();
+        ui.update(-1.0);
+        assert_eq!(ui.shortcut, Some(42));
+        assert_eq!(ui.last_key, None);
+
+        assert_eq!(ui.remove_shortcut(&redo), Status::Success);
+        assert_eq!(ui.remove_shortcut(&redo), Status::Failure);
+    }
+
+    #[test]
+    fn key_repeats() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().add_shortcut("Ctrl+Z".parse().unwrap(), 1);
+        let key_event = |c, modifiers| Event {
+            data: EventType::KeyPress(Key { key: KeyVal::Character(c), modifiers, code: 0, is_repeat: false }),
+            context: EventContext::default()
+        };
+        // repeats ignored by default
+        view.queue_event(key_event('a', Modifiers::NONE));
+        view.handle().update(-1.0);
+        assert!(!view.handle().last_key.unwrap().is_repeat);
+        view.handle().last_key = None;
+        view.queue_event(key_event('a', Modifiers::NONE));
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().last_key, None);
+
+        // shortcuts ignore repeats
+        view.queue_event(key_event('z', Modifiers::CTRL));
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().shortcut, Some(1));
+        view.handle().shortcut = None;
+        view.queue_event(key_event('z', Modifiers::CTRL));
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().shortcut, None);
+        assert_eq!(view.handle().last_key, None);
+
+        view.handle().set_ignore_key_repeats(ViewHintBool::False);
+        view.queue_event(key_event('b', Modifiers::NONE));
+        view.handle().update(-1.0);
+        assert!(!view.handle().last_key.unwrap().is_repeat);
+        view.queue_event(key_event('b', Modifiers::NONE));
+        view.handle().update(-1.0);
+        assert!(view.handle().last_key.unwrap().is_repeat);
+    }
+
+    #[test]
+    fn key_state_reset_on_focus_out() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let shift = KeyVal::Special(SpecialKey::ShiftL);
+        view.queue_event(Event {
+            data: EventType::KeyPress(Key { key: shift, modifiers: Modifiers::NONE, code: 50, is_repeat: false }),
+            context: EventContext::default()
+        });
+        view.queue_event(Event {
+            data: EventType::MouseButtonPress(MouseButton { button: Button::Right, modifiers: Modifiers::SHIFT, click_count: 1 }),
+            context: EventContext::default()
+        });
+        view.handle().update(-1.0);
+        view.handle().update(-1.0);
+        assert_eq!(view.handle().modifiers(), Modifiers::SHIFT);
+        assert!(view.handle().is_key_pressed(shift));
+        assert!(view.handle().is_physical_key_pressed(PhysicalKey::from_keycode(50)));
+        assert_eq!(view.handle().mouse_buttons(), MouseButtons::RIGHT);
+
+        view.fake_focus_out();
+        assert_eq!(view.handle().modifiers(), Modifiers::NONE);
+        assert!(!view.handle().is_key_pressed(shift));
+        assert!(!view.handle().is_physical_key_pressed(PhysicalKey::from_keycode(50)));
+        assert_eq!(view.handle().mouse_buttons(), MouseButtons::NONE);
+    }
+
+    #[test]
+    fn window_configuration() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        assert!(!view.mock_instance().resizable());
+        assert_eq!(view.mock_instance().update_timeout(), None);
+        {
+            let ui = view.handle();
+            ui.set_default_size(320, 200);
+            ui.set_max_size(640, 400);
+            ui.set_aspect_ratio(1, 1, 16, 9);
+            ui.make_resizable();
+            ui.set_cursor(Cursor::Hand);
+            ui.start_timer(7, 0.5);
+            ui.start_timer(2, 1.0);
+            ui.update(0.0);
         }
+        assert!(view.handle().is_resizable());
+        let mock = view.mock_instance();
+        assert_eq!(mock.default_size(), Size { w: 320., h: 200. });
+        assert_eq!(mock.max_size(), Size { w: 640., h: 400. });
+        assert_eq!(mock.aspect_ratio(), (1, 1, 16, 9));
+        assert!(mock.resizable());
+        assert_eq!(mock.cursor(), Cursor::Hand);
+        assert_eq!(mock.pending_timers(), vec![(2, 1.0), (7, 0.5)]);
+        assert_eq!(mock.update_timeout(), Some(0.0));
+        assert!(!mock.realized());
+        view.handle().realize();
+        assert!(view.mock_instance().realized());
+    }
+
+    #[test]
+    fn damage_log() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let rect = Rect { pos: Coord { x: 1., y: 2. }, size: Size { w: 3., h: 4. } };
+        view.handle().post_redisplay();
+        view.handle().post_redisplay_rect(rect.pos, rect.size);
+        assert_eq!(view.mock_instance().damage_log(), &[Damage::View, Damage::Rect(rect)]);
+        view.clear_damage_log();
+        assert!(view.mock_instance().damage_log().is_empty());
+    }
+
+    #[test]
+    fn fake_expose() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.fake_expose(ExposeArea { pos: Coord::default(), size: Size { w: 10., h: 10. } });
+        assert!(view.handle().exposed.is_empty());
+        assert!(view.mock_instance().surface().is_none());
+
+        view.handle().set_default_size(32, 16);
+        view.handle().realize();
+        let area = ExposeArea { pos: Coord { x: 8., y: 0. }, size: Size { w: 4., h: 4. } };
+        view.fake_expose(area);
+        let whole = ExposeArea { pos: Coord::default(), size: Size { w: 32., h: 16. } };
+        view.fake_expose(area);
+        assert_eq!(view.handle().exposed, vec![whole, area]);
+
+        let mock = view.mock_instance();
+        let surface = mock.surface().unwrap();
+        assert_eq!((surface.get_width(), surface.get_height()), (32, 16));
+        assert_eq!(mock.pixel(0, 0), Some(0xff0000ff));
+        assert_eq!(mock.pixel(20, 10), Some(0xffff0000));
+        assert_eq!(mock.pixel(32, 0), None);
+    }
+
+    #[test]
+    fn render_scaled() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        assert!(view.render(Size::default(), 1.0).is_none());
+
+        let surface = view.render(Size { w: 16., h: 8. }, 2.0).unwrap();
+        assert_eq!((surface.get_width(), surface.get_height()), (32, 16));
+        assert_eq!(view.handle().exposed, vec![ExposeArea { pos: Coord::default(), size: Size { w: 16., h: 8. } }]);
+        assert_eq!(view.mock_instance().pixel(15, 15), Some(0xff0000ff));
+        assert_eq!(view.mock_instance().pixel(16, 15), Some(0xffff0000));
+    }
+
+    #[test]
+    fn snapshot() {
+        let path = std::env::temp_dir().join("pugl-sys-view-snapshot.png");
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.render(Size { w: 16., h: 8. }, 1.0).unwrap().write_to_png(&mut std::fs::File::create(&path).unwrap()).unwrap();
+
+        view.assert_snapshot(&path, Size { w: 16., h: 8. }, 1.0, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn expose_after_redisplay() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().set_default_size(32, 16);
+        view.handle().show_window();
+        view.fake_expose(ExposeArea { pos: Coord::default(), size: Size { w: 32., h: 16. } });
+        view.handle().exposed.clear();
+
+        view.handle().update(0.0);
+        assert!(view.handle().exposed.is_empty());
+
+        view.queue_event(Event { data: EventType::MouseMove(MotionContext::default()), context: EventContext::default() });
+        view.handle().post_redisplay_rect(Coord { x: 2., y: 2. }, Size { w: 2., h: 2. });
+        view.handle().post_redisplay_rect(Coord { x: 30., y: 10. }, Size { w: 4., h: 4. });
+        view.handle().update(0.0);
+        assert_eq!(view.handle().exposed, vec![ExposeArea { pos: Coord { x: 2., y: 2. }, size: Size { w: 30., h: 12. } }]);
+
+        let path = std::env::temp_dir().join("pugl-sys-expose-after-redisplay.png");
+        view.mock_instance().save_png(&path).unwrap();
+        let png = cairo::ImageSurface::create_from_png(&mut std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!((png.get_width(), png.get_height()), (32, 16));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn timers_fire_in_order() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.handle().start_timer(3, 0.25);
+        view.handle().start_timer(1, 0.125);
+        view.handle().start_timer(2, 0.25);
+
+        view.advance_time(0.0625);
+        assert!(view.handle().timer_events.is_empty());
+
+        view.advance_time(0.4375);
+        assert_eq!(view.handle().timer_events, vec![1, 1, 2, 3, 1, 1, 2]);
+        assert_eq!(view.mock_instance().time(), 0.5);
+
+        assert_eq!(view.handle().stop_timer(1), Status::Success);
+        assert_eq!(view.handle().stop_timer(3), Status::Failure);
+        view.handle().timer_events.clear();
+        view.advance_time(0.25);
+        assert_eq!(view.handle().timer_events, vec![2]);
+    }
+
+    #[test]
+    fn non_finite_timer_rejected() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        assert_eq!(view.handle().start_timer(1, f64::NAN), Status::BadParameter);
+        assert_eq!(view.handle().start_timer(2, f64::INFINITY), Status::BadParameter);
+        view.handle().start_timer(3, 0.25);
+        view.advance_time(0.25);
+        assert_eq!(view.handle().timer_events, vec![3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot advance the time by NaN")]
+    fn advance_time_by_nan() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.advance_time(f64::NAN);
+    }
+
+    #[test]
+    fn queued_events_stamped_with_time() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let event = Event {
+            data: EventType::MouseMove(MotionContext::default()),
+            context: EventContext { time: 42.0, ..EventContext::default() }
+        };
+        view.queue_event(event);
+        view.advance_time(1.5);
+        view.queue_event(event);
+
+        view.handle().update(0.0);
+        view.handle().update(0.0);
+        assert_eq!(view.handle().event_times, vec![0.0, 1.5]);
+    }
+
+    #[test]
+    fn update_drains_events_then_timers_then_expose() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        view.fake_resize(Size { w: 32., h: 16. });
+        view.handle().start_timer(1, 0.125);
+        view.handle().post_redisplay();
+        view.click(Coord { x: 1., y: 1. });
+
+        view.handle().update(0.25);
+        assert_eq!(view.handle().calls, vec!["event", "event", "event", "event", "timer_event", "timer_event", "exposed"]);
+        assert_eq!(view.mock_instance().time(), 0.25);
+        assert_eq!(view.mock_instance().update_count(), 1);
+
+        view.handle().calls.clear();
+        view.handle().update(0.0);
+        assert!(view.handle().calls.is_empty());
+    }
+
+    #[test]
+    fn update_modes() {
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        assert_eq!(view.mock_instance().update_mode(), None);
+
+        view.handle().update(0.0);
+        assert_eq!(view.mock_instance().update_mode(), Some(UpdateMode::Polling));
+        view.handle().update(0.5);
+        assert_eq!(view.mock_instance().update_mode(), Some(UpdateMode::Timeout(0.5)));
+        assert_eq!(view.mock_instance().time(), 0.5);
+
+        // blocking waits for the next timer if there is nothing else to do
+        view.handle().start_timer(3, 0.25);
+        view.handle().start_timer(4, 1.0);
+        view.handle().update(-1.0);
+        assert_eq!(view.mock_instance().update_mode(), Some(UpdateMode::Blocking));
+        assert_eq!(view.handle().timer_events, vec![3]);
+        assert_eq!(view.mock_instance().time(), 0.75);
+
+        // no timer left that could wake it up before an event
+        view.handle().stop_timer(4);
+        view.handle().update(-1.0);
+        assert_eq!(view.mock_instance().time(), 0.75);
+        assert_eq!(view.mock_instance().update_count(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir().join("pugl-sys-record-and-replay.jsonl");
+        let mut view = make_view();
+        view.start_recording(std::fs::File::create(&path).unwrap());
+        view.fake_resize(Size { w: 100., h: 50. });
+        view.fake_focus_in();
+        view.handle().start_timer(5, 0.5);
+        view.click(Coord { x: 10., y: 10. });
+        view.handle().update(0.5);
+        view.click(Coord { x: 10., y: 10. });
+        view.handle().update(0.0);
+        view.stop_recording().unwrap();
+        // not recorded any more
+        view.fake_focus_out();
+
+        let records = read_recording(std::io::BufReader::new(std::fs::File::open(&path).unwrap())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(records.len(), 2 + 4 + 1 + 2);
+        assert_eq!(records[0], Record { time: 0., event: RecordedEvent::Configure(Size { w: 100., h: 50. }) });
+        assert_eq!(records[6], Record { time: 0.5, event: RecordedEvent::Timer(5) });
+
+        let mut replayed = make_view();
+        replayed.handle().start_timer(5, 0.5);
+        replayed.replay_recording(&records);
+        assert_eq!(replayed.mock_instance().frame.size, Size { w: 100., h: 50. });
+        assert_eq!(replayed.mock_instance().time(), records.last().unwrap().time);
+
+        let (original, replayed) = (view.handle(), replayed.handle());
+        assert_eq!(replayed.timer_events, vec![5]);
+        assert_eq!(replayed.events, original.events);
+        assert_eq!(replayed.event_times, original.event_times);
+        assert_eq!(replayed.click_count, 1);
+    }
+
+    #[test]
+    fn replay_fires_timers_between_records() {
+        let mut view = scripted_view();
+        view.click(Coord { x: 10., y: 10. });
+        view.handle().update(0.0);
+        view.advance_time(1.0);
+        view.click(Coord { x: 10., y: 10. });
+        view.handle().update(0.0);
+        let ui = view.handle();
+        let records: Vec<Record> = ui.events.iter().zip(ui.event_times.iter())
+            .map(|(&data, &time)| Record {
+                time,
+                event: RecordedEvent::Input(Event { data, context: EventContext { time, ..Default::default() } })
+            })
+            .collect();
+
+        let mut replayed = make_view();
+        replayed.handle().start_timer(1, 0.25);
+        replayed.replay_recording(&records[..4]);
+        assert!(replayed.handle().timer_events.is_empty());
+        replayed.replay_recording(&records[4..]);
+        assert_eq!(replayed.handle().timer_events, vec![1, 1, 1, 1]);
+        assert_eq!(replayed.mock_instance().time(), records.last().unwrap().time);
+
+        replayed.handle().timer_events.clear();
+        replayed.handle().update(0.0);
+        assert!(replayed.handle().timer_events.is_empty());
+    }
+
+    fn scripted_view() -> Box<PuglView<UI, MockBackend>> {
+        let mut view = make_view();
+        view.fake_resize(Size { w: 100., h: 50. });
+        view
+    }
+
+    fn press(button: Button) -> EventType {
+        EventType::MouseButtonPress(MouseButton { button, modifiers: Modifiers::NONE, click_count: 1 })
+    }
+
+    fn release(button: Button) -> EventType {
+        EventType::MouseButtonRelease(MouseButton { button, modifiers: Modifiers::NONE, click_count: 1 })
+    }
+
+    fn motion(buttons: MouseButtons) -> EventType {
+        EventType::MouseMove(MotionContext { buttons, ..MotionContext::default() })
+    }
+
+    fn key(key: KeyVal, modifiers: Modifiers, press: bool) -> EventType {
+        let key = Key { key, modifiers, code: 0, is_repeat: false };
+        if press { EventType::KeyPress(key) } else { EventType::KeyRelease(key) }
+    }
+
+    #[test]
+    fn script_click() {
+        let mut view = scripted_view();
+        view.advance_time(1.0);
+        view.click(Coord { x: 10., y: 20. });
+        view.click(Coord { x: 10., y: 20. });
+        view.handle().update(0.0);
+
+        let ui = view.handle();
+        assert_eq!(ui.events, vec![
+            EventType::PointerIn(CrossingMode::Normal),
+            motion(MouseButtons::NONE),
+            press(Button::Left),
+            release(Button::Left),
+            EventType::MouseButtonPress(MouseButton { button: Button::Left, modifiers: Modifiers::NONE, click_count: 2 }),
+            EventType::MouseButtonRelease(MouseButton { button: Button::Left, modifiers: Modifiers::NONE, click_count: 2 }),
+        ]);
+        assert_eq!(ui.event_times[0], 1.0);
+        assert!(ui.event_times.windows(2).all(|t| t[0] < t[1]));
+        assert_eq!(ui.click_count, 2);
+    }
+
+    #[test]
+    fn script_click_without_hover() {
+        let mut view = scripted_view();
+        view.click(Coord { x: 40., y: 30. });
+        view.click(Coord { x: 200., y: 30. });
+
+        let events: Vec<(EventType, Coord)> = view.mock_instance().event_queue.iter().map(|ev| (ev.data, ev.pos())).collect();
+        let (inside, outside) = (Coord { x: 40., y: 30. }, Coord { x: 200., y: 30. });
+        assert_eq!(events, vec![
+            (EventType::PointerIn(CrossingMode::Normal), inside),
+            (motion(MouseButtons::NONE), inside),
+            (press(Button::Left), inside),
+            (release(Button::Left), inside),
+            (EventType::PointerOut(CrossingMode::Normal), outside),
+            (press(Button::Left), outside),
+            (release(Button::Left), outside),
+        ]);
+    }
+
+    #[test]
+    fn script_hover() {
+        let mut view = scripted_view();
+        view.hover(Coord { x: 10., y: 10. });
+        view.hover(Coord { x: 10., y: 10. });
+        view.hover(Coord { x: 20., y: 10. });
+        view.hover(Coord { x: 200., y: 10. });
+        view.hover(Coord { x: 300., y: 10. });
+        view.handle().update(0.0);
+
+        assert_eq!(view.handle().events, vec![
+            EventType::PointerIn(CrossingMode::Normal),
+            motion(MouseButtons::NONE),
+            motion(MouseButtons::NONE),
+            EventType::PointerOut(CrossingMode::Normal),
+        ]);
+        assert!(!view.handle().pointer_entered);
+    }
+
+    #[test]
+    fn script_drag() {
+        let mut view = scripted_view();
+        view.drag(Coord { x: 10., y: 10. }, Coord { x: 150., y: 30. }, 2);
+        view.handle().update(0.0);
+
+        assert_eq!(view.handle().events, vec![
+            EventType::PointerIn(CrossingMode::Normal),
+            motion(MouseButtons::NONE),
+            press(Button::Left),
+            motion(MouseButtons::LEFT),
+            motion(MouseButtons::LEFT),
+            release(Button::Left),
+            EventType::PointerOut(CrossingMode::Normal),
+        ]);
+        let mock = view.mock_instance();
+        assert!(mock.event_queue.is_empty());
+    }
+
+    #[test]
+    fn script_drag_positions() {
+        let mut view = scripted_view();
+        view.drag(Coord { x: 10., y: 10. }, Coord { x: 30., y: 20. }, 2);
+        let positions: Vec<Coord> = view.mock_instance().event_queue.iter().map(|ev| ev.pos()).collect();
+        assert_eq!(positions, vec![
+            Coord { x: 10., y: 10. },
+            Coord { x: 10., y: 10. },
+            Coord { x: 10., y: 10. },
+            Coord { x: 20., y: 15. },
+            Coord { x: 30., y: 20. },
+            Coord { x: 30., y: 20. },
+        ]);
+    }
+
+    #[test]
+    fn script_type_text() {
+        let mut view = scripted_view();
+        view.type_text("a1");
+        view.handle().update(0.0);
+
+        assert_eq!(view.handle().events, vec![
+            key(KeyVal::Character('a'), Modifiers::NONE, true),
+            key(KeyVal::Character('a'), Modifiers::NONE, false),
+            key(KeyVal::Character('1'), Modifiers::NONE, true),
+            key(KeyVal::Character('1'), Modifiers::NONE, false),
+        ]);
+    }
+
+    #[test]
+    fn script_type_shifted_text() {
+        let mut view = scripted_view();
+        view.type_text("A!");
+        view.handle().update(0.0);
+
+        let shift = KeyVal::Special(SpecialKey::ShiftL);
+        assert_eq!(view.handle().events, vec![
+            key(shift, Modifiers::NONE, true),
+            key(KeyVal::Character('a'), Modifiers::SHIFT, true),
+            key(KeyVal::Character('a'), Modifiers::SHIFT, false),
+            key(shift, Modifiers::SHIFT, false),
+            key(shift, Modifiers::NONE, true),
+            key(KeyVal::Character('1'), Modifiers::SHIFT, true),
+            key(KeyVal::Character('1'), Modifiers::SHIFT, false),
+            key(shift, Modifiers::SHIFT, false),
+        ]);
+        assert_eq!(view.handle().modifiers(), Modifiers::NONE);
+        assert_eq!(unshifted('|'), Some('\\'));
+        assert_eq!(unshifted('"'), Some('\''));
+        assert_eq!(unshifted('a'), None);
+    }
+
+    #[test]
+    fn script_shortcut() {
+        let mut view = scripted_view();
+        let shortcut: Shortcut = "Ctrl+Shift+s".parse().unwrap();
+        view.handle().add_shortcut(shortcut, 7);
+        view.press(shortcut);
+        view.handle().update(0.0);
+
+        let ui = view.handle();
+        assert_eq!(ui.shortcut, Some(7));
+        let ctrl_shift = Modifiers::CTRL | Modifiers::SHIFT;
+        assert_eq!(ui.events, vec![
+            key(KeyVal::Special(SpecialKey::CtrlL), Modifiers::NONE, true),
+            key(KeyVal::Special(SpecialKey::ShiftL), Modifiers::CTRL, true),
+            key(KeyVal::Character('s'), ctrl_shift, false),
+            key(KeyVal::Special(SpecialKey::ShiftL), ctrl_shift, false),
+            key(KeyVal::Special(SpecialKey::CtrlL), Modifiers::CTRL, false),
+        ]);
+        assert_eq!(ui.modifiers(), Modifiers::NONE);
+    }
+
+    #[test]
+    fn script_scroll() {
+        let mut view = scripted_view();
+        view.scroll(Coord { x: 5., y: 5. }, -2.0);
+        view.handle().update(0.0);
+
+        let scroll = Scroll { dx: 0., dy: -2.0, modifiers: Modifiers::NONE, direction: ScrollDirection::Down, phase: ScrollPhase::None };
+        assert_eq!(view.handle().events, vec![
+            EventType::PointerIn(CrossingMode::Normal),
+            motion(MouseButtons::NONE),
+            EventType::Scroll(scroll),
+        ]);
     }
 
     #[test]
     fn view_hint_use_compat_profile() {
-        let mut view = PuglView::<UI>::new(std::ptr::null_mut(), |pv| UI::new(pv));
+        let mut view = PuglView::<UI, MockBackend>::new(std::ptr::null_mut(), |pv| UI::new(pv));
         let ui = view.handle();
         //ui.set_view_hint();
 